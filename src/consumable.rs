@@ -0,0 +1,270 @@
+//! 食物与药水等消耗品带来的属性加成。
+//!
+//! 与游戏中一致，每一项加成都按装备专家之证后的属性的百分比计算（向下取整），并且不超过各自的上限；
+//! 食物与药水的加成分别由同一组属性计算后相加，互不影响。
+//! 这里的字段与游戏中ItemFood表的`Value`、`Max`、`ValueHQ`、`MaxHQ`一一对应。
+//! 本crate不内置消耗品的数据表，具体的数值需要由调用者从游戏数据中读取。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::consumable::{self, Bonuses, Consumable, ConsumableKind, StatBonus};
+//! use ffxiv_crafting::{data, Attributes, Recipe, Status};
+//!
+//! let food = Consumable {
+//!     kind: ConsumableKind::Food,
+//!     nq: Bonuses {
+//!         control: StatBonus::new(4, 56),
+//!         craft_points: StatBonus::new(21, 60),
+//!         ..Default::default()
+//!     },
+//!     hq: Bonuses {
+//!         control: StatBonus::new(5, 70),
+//!         craft_points: StatBonus::new(26, 75),
+//!         ..Default::default()
+//!     },
+//! };
+//! let base = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4000,
+//!     control: 3900,
+//!     craft_points: 560,
+//!     specialist: false,
+//! };
+//! let attr = consumable::effective_attributes(&base, false, Some((&food, true)), None).unwrap();
+//! assert_eq!(attr.control, 3900 + 70);
+//! assert_eq!(attr.craft_points, 560 + 75);
+//!
+//! // 专家之证的加成先于食物计算，制作力的百分比加成随之增加
+//! let attr = consumable::effective_attributes(&base, true, Some((&food, true)), None).unwrap();
+//! assert_eq!(attr.craft_points, 560 + 15 + 75);
+//!
+//! let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! let s = Status::new(attr, recipe);
+//! ```
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use crate::Attributes;
+
+/// 一项属性的加成：增加基础属性的`percent`%，但不超过`max`
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub struct StatBonus {
+    pub percent: i32,
+    pub max: i32,
+}
+
+impl StatBonus {
+    pub const fn new(percent: i32, max: i32) -> Self {
+        Self { percent, max }
+    }
+
+    /// 对基础属性`base`的加成值
+    pub fn value(&self, base: i32) -> i32 {
+        (base * self.percent / 100).min(self.max).max(0)
+    }
+}
+
+/// 一件消耗品在某一品质下对各项属性的加成，没有加成的属性为默认值
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub struct Bonuses {
+    /// 作业精度
+    pub craftsmanship: StatBonus,
+    /// 加工精度
+    pub control: StatBonus,
+    /// 制作力
+    pub craft_points: StatBonus,
+}
+
+/// 消耗品的种类，同一种类的消耗品同时只能生效一个
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum ConsumableKind {
+    /// 食物
+    Food,
+    /// 药水
+    Medicine,
+}
+
+/// 一件消耗品
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Consumable {
+    pub kind: ConsumableKind,
+    /// 普通品质（NQ）的效果
+    pub nq: Bonuses,
+    /// 高品质（HQ）的效果
+    pub hq: Bonuses,
+}
+
+impl Consumable {
+    /// 按照品质选择效果，`high_quality`为true时选择HQ
+    pub fn bonuses(&self, high_quality: bool) -> Bonuses {
+        if high_quality {
+            self.hq
+        } else {
+            self.nq
+        }
+    }
+}
+
+/// 消耗品放在了不属于它的位置上，如把药水当作食物使用
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConsumableKindError {
+    /// 该位置要求的种类
+    pub expected: ConsumableKind,
+    /// 实际传入的消耗品的种类
+    pub found: ConsumableKind,
+}
+
+impl Display for ConsumableKindError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {:?}, found {:?}", self.expected, self.found)
+    }
+}
+
+impl Error for ConsumableKindError {}
+
+/// 由基础属性`base`以及选用的食物与药水计算实际生效的属性，可以直接用于 [`Status::new`](crate::Status::new)。
+///
+/// 游戏中先加上专家之证的加成，再由此计算食物与药水的百分比加成。
+/// `specialist`为true时按专家计算，`base`尚不是专家时在这里加上 [`Attributes::SPECIALIST_BONUS`]；
+/// 为false时按非专家计算，`base`已经是专家时先去掉这部分加成。
+///
+/// 食物与药水分别以`(消耗品, 是否为HQ)`的形式传入。
+/// 同一种类的消耗品同时只能生效一个，因此消耗品的种类与所在的位置不符时返回错误。
+pub fn effective_attributes(
+    base: &Attributes,
+    specialist: bool,
+    food: Option<(&Consumable, bool)>,
+    medicine: Option<(&Consumable, bool)>,
+) -> Result<Attributes, ConsumableKindError> {
+    let base = if specialist {
+        base.with_specialist()
+    } else {
+        base.without_specialist()
+    };
+    let mut attr = base;
+    for (expected, selected) in [
+        (ConsumableKind::Food, food),
+        (ConsumableKind::Medicine, medicine),
+    ] {
+        let Some((consumable, high_quality)) = selected else {
+            continue;
+        };
+        if consumable.kind != expected {
+            return Err(ConsumableKindError {
+                expected,
+                found: consumable.kind,
+            });
+        }
+        let b = consumable.bonuses(high_quality);
+        attr.craftsmanship += b.craftsmanship.value(base.craftsmanship);
+        attr.control += b.control.value(base.control);
+        attr.craft_points += b.craft_points.value(base.craft_points);
+    }
+    Ok(attr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        effective_attributes, Bonuses, Consumable, ConsumableKind, ConsumableKindError, StatBonus,
+    };
+    use crate::Attributes;
+
+    #[test]
+    fn stacking() {
+        let base = Attributes {
+            level: 100,
+            craftsmanship: 4321,
+            control: 1000,
+            craft_points: 500,
+            specialist: false,
+        };
+        let food = Consumable {
+            kind: ConsumableKind::Food,
+            nq: Bonuses {
+                craftsmanship: StatBonus::new(3, 1000),
+                control: StatBonus::new(5, 40),
+                ..Default::default()
+            },
+            hq: Bonuses::default(),
+        };
+        let medicine = Consumable {
+            kind: ConsumableKind::Medicine,
+            nq: Bonuses {
+                craft_points: StatBonus::new(3, 13),
+                ..Default::default()
+            },
+            hq: Bonuses {
+                craft_points: StatBonus::new(6, 21),
+                ..Default::default()
+            },
+        };
+        assert_eq!(effective_attributes(&base, false, None, None), Ok(base));
+
+        let attr =
+            effective_attributes(&base, false, Some((&food, false)), Some((&medicine, false)))
+                .unwrap();
+        // 百分比加成向下取整
+        assert_eq!(attr.craftsmanship, 4321 + 129);
+        assert_eq!(attr.control, 1000 + 40);
+        assert_eq!(attr.craft_points, 500 + 13);
+
+        let attr = effective_attributes(&base, false, None, Some((&medicine, true))).unwrap();
+        assert_eq!(attr.craft_points, 500 + 21);
+        assert_eq!(attr.level, base.level);
+
+        // 两份食物不能同时生效
+        assert_eq!(
+            effective_attributes(&base, false, Some((&food, false)), Some((&food, false))),
+            Err(ConsumableKindError {
+                expected: ConsumableKind::Medicine,
+                found: ConsumableKind::Food,
+            })
+        );
+        assert_eq!(
+            effective_attributes(&base, false, Some((&medicine, true)), None),
+            Err(ConsumableKindError {
+                expected: ConsumableKind::Food,
+                found: ConsumableKind::Medicine,
+            })
+        );
+    }
+
+    #[test]
+    fn specialist() {
+        let base = Attributes::new(100, 4000, 3980, 600);
+        let food = Consumable {
+            kind: ConsumableKind::Food,
+            nq: Bonuses {
+                craftsmanship: StatBonus::new(5, 1000),
+                control: StatBonus::new(5, 1000),
+                craft_points: StatBonus::new(10, 1000),
+            },
+            hq: Bonuses::default(),
+        };
+        // 百分比加成按加上专家之证后的属性计算
+        let attr = effective_attributes(&base, true, Some((&food, false)), None).unwrap();
+        assert!(attr.specialist);
+        assert_eq!(attr.craftsmanship, 4020 + 201);
+        assert_eq!(attr.control, 4000 + 200);
+        assert_eq!(attr.craft_points, 615 + 61);
+
+        // 传入已经是专家的属性时不重复计算专家之证
+        let specialist = base.with_specialist();
+        let same = effective_attributes(&specialist, true, Some((&food, false)), None);
+        assert_eq!(same, Ok(attr));
+        let plain = effective_attributes(&specialist, false, Some((&food, false)), None).unwrap();
+        assert_eq!(
+            Ok(plain),
+            effective_attributes(&base, false, Some((&food, false)), None)
+        );
+        assert_eq!(plain.craft_points, 600 + 60);
+    }
+}
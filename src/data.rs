@@ -1,4 +1,4 @@
-use crate::RecipeLevel;
+use crate::{Actions, RecipeLevel};
 
 /*
 // Run this in Deno
@@ -923,3 +923,47 @@ pub fn high_quality_table(percent: u32) -> Option<i32> {
         _ => None,
     }
 }
+
+/// 各技能在游戏客户端中的名称，每项依次为：英语、日语、德语、法语、中文、韩语。
+///
+/// 表中不包含用于模拟失败结果的假技能。
+#[rustfmt::skip]
+pub(crate) const ACTION_NAMES: [(Actions, [&str; 6]); 37] = [
+    (Actions::BasicSynthesis, ["Basic Synthesis", "作業", "Bearbeiten", "Travail de base", "制作", "작업"]),
+    (Actions::BasicTouch, ["Basic Touch", "加工", "Veredelung", "Ouvrage de base", "加工", "가공"]),
+    (Actions::MastersMend, ["Master's Mend", "マスターズメンド", "Wiederherstellung", "Réparation de maître", "精修", "능숙한 땜질"]),
+    (Actions::HastyTouch, ["Hasty Touch", "ヘイスティタッチ", "Hastige Veredelung", "Ouvrage hâtif", "仓促", "성급한 손길"]),
+    (Actions::RapidSynthesis, ["Rapid Synthesis", "突貫作業", "Schnelle Bearbeitung", "Travail hâtif", "高速制作", "고속 작업"]),
+    (Actions::Observe, ["Observe", "経過観察", "Beobachten", "Observation", "观察", "경과 관찰"]),
+    (Actions::TricksOfTheTrade, ["Tricks of the Trade", "秘訣", "Kniff", "Ficelles du métier", "秘诀", "비결"]),
+    (Actions::WasteNot, ["Waste Not", "倹約", "Nachhaltigkeit", "Parcimonie", "俭约", "근검절약"]),
+    (Actions::Veneration, ["Veneration", "ヴェネレーション", "Ehrfurcht", "Vénération", "崇敬", "공경"]),
+    (Actions::StandardTouch, ["Standard Touch", "中級加工", "Solide Veredelung", "Ouvrage standard", "中级加工", "중급 가공"]),
+    (Actions::GreatStrides, ["Great Strides", "グレートストライド", "Große Schritte", "Grands progrès", "阔步", "장족의 발전"]),
+    (Actions::Innovation, ["Innovation", "イノベーション", "Innovation", "Innovation", "改革", "혁신"]),
+    (Actions::FinalAppraisal, ["Final Appraisal", "最終確認", "Schlussbewertung", "Évaluation finale", "最终确认", "최종 확인"]),
+    (Actions::WasteNotII, ["Waste Not II", "長期倹約", "Nachhaltigkeit II", "Parcimonie pérenne", "长期俭约", "장기 절약"]),
+    (Actions::ByregotsBlessing, ["Byregot's Blessing", "ビエルゴの祝福", "Byregots Segen", "Bénédiction de Byregot", "比尔格的祝福", "비레고의 축복"]),
+    (Actions::PreciseTouch, ["Precise Touch", "集中加工", "Präzise Veredelung", "Ouvrage précis", "集中加工", "집중 가공"]),
+    (Actions::MuscleMemory, ["Muscle Memory", "確信", "Motorisches Gedächtnis", "Mémoire musculaire", "坚信", "확신"]),
+    (Actions::CarefulSynthesis, ["Careful Synthesis", "模範作業", "Sorgfältige Bearbeitung", "Travail prudent", "模范制作", "모범 작업"]),
+    (Actions::Manipulation, ["Manipulation", "マニピュレーション", "Manipulation", "Manipulation", "掌握", "교묘한 손놀림"]),
+    (Actions::PrudentTouch, ["Prudent Touch", "倹約加工", "Nachhaltige Veredelung", "Ouvrage parcimonieux", "俭约加工", "절약 가공"]),
+    (Actions::AdvancedTouch, ["Advanced Touch", "上級加工", "Höhere Veredelung", "Ouvrage avancé", "上级加工", "상급 가공"]),
+    (Actions::Reflect, ["Reflect", "真価", "Einkehr", "Véritable valeur", "闲静", "진가"]),
+    (Actions::PreparatoryTouch, ["Preparatory Touch", "下地加工", "Basisveredelung", "Ouvrage préparatoire", "坯料加工", "밑작업 가공"]),
+    (Actions::Groundwork, ["Groundwork", "下地作業", "Vorarbeit", "Travail préparatoire", "坯料制作", "밑작업"]),
+    (Actions::DelicateSynthesis, ["Delicate Synthesis", "精密作業", "Akribie", "Travail minutieux", "精密制作", "정밀 작업"]),
+    (Actions::IntensiveSynthesis, ["Intensive Synthesis", "集中作業", "Präzise Bearbeitung", "Travail vigilant", "集中制作", "집중 작업"]),
+    (Actions::TrainedEye, ["Trained Eye", "匠の早業", "Flinke Hand", "Œil expérimenté", "工匠的神速技巧", "장인의 날랜손"]),
+    (Actions::PrudentSynthesis, ["Prudent Synthesis", "倹約作業", "Nachhaltige Bearbeitung", "Travail économe", "俭约制作", "절약 작업"]),
+    (Actions::TrainedFinesse, ["Trained Finesse", "匠の神業", "Meisterliche Veredelung", "Doigté expérimenté", "工匠的神技", "장인의 신기"]),
+    (Actions::CarefulObservation, ["Careful Observation", "設計変更", "Sorgfältige Beobachtung", "Observation méticuleuse", "设计变动", "설계 변경"]),
+    (Actions::HeartAndSoul, ["Heart and Soul", "一心不乱", "Mit Leib und Seele", "Attention totale", "专心致志", "일심불란"]),
+    (Actions::RefinedTouch, ["Refined Touch", "洗練加工", "Raffinierte Veredelung", "Ouvrage raffiné", "精炼加工", "세련 가공"]),
+    (Actions::DaringTouch, ["Daring Touch", "デアリングタッチ", "Riskante Veredelung", "Ouvrage audacieux", "冒进", "과감한 손길"]),
+    (Actions::QuickInnovation, ["Quick Innovation", "クイックイノベーション", "Schnelle Innovation", "Innovation rapide", "快速改革", "신속한 혁신"]),
+    (Actions::ImmaculateMend, ["Immaculate Mend", "パーフェクトメンド", "Perfekte Wiederherstellung", "Réparation impeccable", "巧夺天工", "완벽한 땜질"]),
+    (Actions::TrainedPerfection, ["Trained Perfection", "匠の絶技", "Meisterliche Perfektion", "Perfection experte", "工匠的绝技", "장인의 묘기"]),
+    (Actions::StellarSteadyHand, ["Stellar Steady Hand", "ステラー・ステディハンド", "Stellare ruhige Hand", "Main stable stellaire", "宇宙稳手", "별빛 안정된 손길"]),
+];
@@ -0,0 +1,529 @@
+//! 评估固定技能序列在随机球色与随机成功率下的表现。
+//!
+//! [`monte_carlo`]通过随机模拟进行评估，[`expected`]则遍历所有可能的分支精确计算期望值。
+//! 两者都与游戏中宏的行为一致：无法发动的技能会被跳过，之后的技能继续执行。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{data, evaluate, Actions, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 594,
+//!     specialist: false,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+//! let s = Status::new(attr, recipe);
+//! let actions = [Actions::Veneration, Actions::HastyTouch, Actions::Groundwork, Actions::Groundwork];
+//! let report = evaluate::monte_carlo(&s, &actions, 1000, 42);
+//! println!("完成率：{}", report.completion_rate());
+//! println!("品质中位数：{:?}", report.quality_percentile(50.0));
+//! println!("失败原因：{:?}", report.failure_counts());
+//!
+//! // 任意一次模拟都可以通过其种子复现
+//! let run = &report.runs[10];
+//! assert_eq!(evaluate::run_once(&s, &actions, run.seed).quality, run.quality);
+//! ```
+use std::collections::{BTreeMap, HashMap};
+
+use crate::objective::Objective;
+use crate::random::{CraftRng, SplitMix64};
+use crate::{Actions, CastActionError, ConditionModel, StateKey, Status};
+
+/// 一次模拟中制作失败的原因
+#[derive(Debug)]
+pub enum RunFailure {
+    /// 第`index`个技能发动后耐久耗尽，制作失败
+    DurabilityExhausted { index: usize },
+    /// 技能序列结束时进展仍未推满
+    ProgressIncomplete,
+}
+
+impl RunFailure {
+    /// 不含技能位置的失败原因，用于统计
+    pub fn reason(&self) -> FailureReason {
+        match self {
+            RunFailure::DurabilityExhausted { .. } => FailureReason::DurabilityExhausted,
+            RunFailure::ProgressIncomplete => FailureReason::ProgressIncomplete,
+        }
+    }
+}
+
+/// 失败原因的分类
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum FailureReason {
+    /// 耐久耗尽
+    DurabilityExhausted,
+    /// 技能序列结束时进展仍未推满
+    ProgressIncomplete,
+}
+
+/// 一次模拟中因无法发动而被跳过的技能
+#[derive(Debug)]
+pub struct SkippedStep {
+    /// 该技能在技能序列中的位置
+    pub index: usize,
+    pub action: Actions,
+    pub error: CastActionError,
+}
+
+/// 一次模拟的结果
+#[derive(Debug)]
+pub struct RunResult {
+    /// 本次模拟使用的随机种子，传给 [`run_once`] 即可复现
+    pub seed: u64,
+    /// 最终进展
+    pub progress: u16,
+    /// 最终品质
+    pub quality: u32,
+    /// 制作完成时的HQ概率（百分数），未完成时为0
+    pub high_quality: i32,
+    /// 失败原因，制作完成时为None
+    pub failure: Option<RunFailure>,
+    /// 因无法发动而被跳过的技能
+    pub skipped: Vec<SkippedStep>,
+    /// 模拟结束时的状态
+    pub status: Status,
+}
+
+/// 多次模拟的结果
+#[derive(Debug)]
+pub struct MonteCarloReport {
+    /// 每一次模拟的结果
+    pub runs: Vec<RunResult>,
+}
+
+impl MonteCarloReport {
+    /// 制作完成的比例
+    pub fn completion_rate(&self) -> f64 {
+        self.mean(|r| r.failure.is_none() as u32 as f64)
+    }
+
+    /// 最终进展的平均值
+    pub fn mean_progress(&self) -> f64 {
+        self.mean(|r| r.progress as f64)
+    }
+
+    /// 最终品质的平均值
+    pub fn mean_quality(&self) -> f64 {
+        self.mean(|r| r.quality as f64)
+    }
+
+    /// HQ概率（百分数）的平均值，未完成的制作计为0
+    pub fn mean_high_quality(&self) -> f64 {
+        self.mean(|r| r.high_quality as f64)
+    }
+
+    /// `objective`的平均分数
+    pub fn mean_score<O: Objective + ?Sized>(&self, objective: &O) -> f64 {
+        self.mean(|r| objective.score(&r.status))
+    }
+
+    /// 最终进展的第`p`百分位数（`p`取0~100），没有模拟结果时返回None
+    pub fn progress_percentile(&self, p: f64) -> Option<u16> {
+        self.percentile(p, |r| r.progress)
+    }
+
+    /// 最终品质的第`p`百分位数（`p`取0~100），没有模拟结果时返回None
+    pub fn quality_percentile(&self, p: f64) -> Option<u32> {
+        self.percentile(p, |r| r.quality)
+    }
+
+    /// HQ概率的第`p`百分位数（`p`取0~100），没有模拟结果时返回None
+    pub fn high_quality_percentile(&self, p: f64) -> Option<i32> {
+        self.percentile(p, |r| r.high_quality)
+    }
+
+    /// 最终进展的分布，键为宽度为`width`的区间的下界，值为落在该区间内的次数
+    pub fn progress_histogram(&self, width: u16) -> BTreeMap<u16, usize> {
+        self.histogram(|r| r.progress - r.progress % width.max(1))
+    }
+
+    /// 最终品质的分布，键为宽度为`width`的区间的下界，值为落在该区间内的次数
+    pub fn quality_histogram(&self, width: u32) -> BTreeMap<u32, usize> {
+        self.histogram(|r| r.quality - r.quality % width.max(1))
+    }
+
+    /// HQ概率的分布，键为HQ概率，值为出现的次数
+    pub fn high_quality_histogram(&self) -> BTreeMap<i32, usize> {
+        self.histogram(|r| r.high_quality)
+    }
+
+    /// 技能序列中第`index`个技能被跳过的比例，对应 [`SkippedAction::probability`]
+    pub fn skip_rate(&self, index: usize) -> f64 {
+        self.mean(|r| r.skipped.iter().any(|x| x.index == index) as u32 as f64)
+    }
+
+    /// 各失败原因出现的次数
+    pub fn failure_counts(&self) -> HashMap<FailureReason, usize> {
+        let mut counts = HashMap::new();
+        for failure in self.runs.iter().filter_map(|r| r.failure.as_ref()) {
+            *counts.entry(failure.reason()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// 最近秩法计算的百分位数
+    fn percentile<T: Ord + Copy>(&self, p: f64, f: impl Fn(&RunResult) -> T) -> Option<T> {
+        let mut values: Vec<T> = self.runs.iter().map(f).collect();
+        values.sort_unstable();
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+        values.get(rank.saturating_sub(1)).copied()
+    }
+
+    fn histogram<T: Ord>(&self, f: impl Fn(&RunResult) -> T) -> BTreeMap<T, usize> {
+        let mut histogram = BTreeMap::new();
+        for r in &self.runs {
+            *histogram.entry(f(r)).or_default() += 1;
+        }
+        histogram
+    }
+
+    fn mean(&self, f: impl Fn(&RunResult) -> f64) -> f64 {
+        if self.runs.is_empty() {
+            return 0.0;
+        }
+        self.runs.iter().map(f).sum::<f64>() / self.runs.len() as f64
+    }
+}
+
+/// 使用给定的随机种子模拟一次技能序列。
+///
+/// 每个工次的球色由 [`ConditionModel`] 随机产生，有成功率的技能会随机决定是否成功。
+/// 与 [`expected`] 相同，无法发动的技能会被跳过并记录在 [`RunResult::skipped`] 中，之后的技能继续执行。
+/// 制作在进展推满或耐久耗尽时结束。
+pub fn run_once(status: &Status, actions: &[Actions], seed: u64) -> RunResult {
+    let model = ConditionModel::from_status(status);
+    let mut rng = SplitMix64::new(seed);
+    let mut s = status.clone();
+    let mut failure = Some(RunFailure::ProgressIncomplete);
+    let mut skipped = Vec::new();
+    for (index, &action) in actions.iter().enumerate() {
+        if let Err(error) = s.try_step_random(action, &model, &mut rng) {
+            skipped.push(SkippedStep {
+                index,
+                action,
+                error,
+            });
+            continue;
+        }
+        if s.progress >= s.recipe.difficulty {
+            failure = None;
+            break;
+        }
+        if s.durability == 0 {
+            failure = Some(RunFailure::DurabilityExhausted { index });
+            break;
+        }
+    }
+    RunResult {
+        seed,
+        progress: s.progress,
+        quality: s.quality,
+        high_quality: match failure {
+            None => s.high_quality_probability().unwrap_or(100),
+            Some(_) => 0,
+        },
+        failure,
+        skipped,
+        status: s,
+    }
+}
+
+/// 使用蒙特卡洛方法将技能序列模拟`runs`次。
+/// 相同的`seed`总会得到相同的结果。
+pub fn monte_carlo(
+    status: &Status,
+    actions: &[Actions],
+    runs: usize,
+    seed: u64,
+) -> MonteCarloReport {
+    let mut rng = SplitMix64::new(seed);
+    MonteCarloReport {
+        runs: (0..runs)
+            .map(|_| run_once(status, actions, rng.next_u64()))
+            .collect(),
+    }
+}
+
+/// 某个技能在部分分支中无法发动而被跳过的情况
+#[derive(Debug)]
+pub struct SkippedAction {
+    /// 该技能在技能序列中的位置
+    pub index: usize,
+    pub action: Actions,
+    /// 该技能被跳过的概率
+    pub probability: f64,
+    /// 跳过的原因，不同分支的原因可能不同，这里给出概率最大的一个
+    pub error: CastActionError,
+}
+
+/// 精确期望值的评估结果
+#[derive(Debug)]
+pub struct ExpectedReport {
+    /// 最终品质的期望
+    pub quality: f64,
+    /// HQ概率（百分数）的期望，未完成的制作计为0
+    pub high_quality: f64,
+    /// 制作完成的概率
+    pub completion: f64,
+    /// 因在部分分支中无法发动而被跳过的技能
+    pub skipped: Vec<SkippedAction>,
+    /// 合并后同时存在的分支数的最大值
+    pub max_branches: usize,
+    /// 技能序列结束时所有可能的状态及其概率
+    pub outcomes: Vec<(f64, Status)>,
+}
+
+impl ExpectedReport {
+    /// `objective`的期望分数
+    pub fn expected_score<O: Objective + ?Sized>(&self, objective: &O) -> f64 {
+        self.outcomes
+            .iter()
+            .map(|(p, s)| p * objective.score(s))
+            .sum()
+    }
+}
+
+/// 遍历技能序列所有可能的分支，精确计算其期望表现。
+///
+/// 每个工次的球色按照 [`ConditionModel`] 分支，有成功率的技能按成功与失败分支，
+/// 结果完全相同的分支会被合并，以限制计算量。
+/// 与游戏中宏的行为以及 [`run_once`] 一致，若某个技能在某个分支中无法发动，则跳过该技能继续执行后续技能，
+/// 跳过的情况及其概率记录在 [`ExpectedReport::skipped`] 中。
+/// 制作在进展推满或耐久耗尽时结束。
+pub fn expected(status: &Status, actions: &[Actions]) -> ExpectedReport {
+    let model = ConditionModel::from_status(status);
+    let mut report = ExpectedReport {
+        quality: 0.0,
+        high_quality: 0.0,
+        completion: 0.0,
+        skipped: Vec::new(),
+        max_branches: 1,
+        outcomes: Vec::new(),
+    };
+    let mut layer: HashMap<StateKey, (f64, Status)> = HashMap::new();
+    layer.insert(status.key(), (1.0, status.clone()));
+    for (index, &action) in actions.iter().enumerate() {
+        let mut next: HashMap<StateKey, (f64, Status)> = HashMap::new();
+        let mut errors: Vec<(f64, CastActionError)> = Vec::new();
+        for (_, (p, s)) in layer {
+            if s.is_finished() {
+                merge(&mut next, p, s);
+                continue;
+            }
+            match s.transitions(action, &model) {
+                Ok(branches) => {
+                    for (pb, s) in branches {
+                        merge(&mut next, p * pb, s);
+                    }
+                }
+                Err(error) => {
+                    match errors.iter_mut().find(|(_, e)| *e == error) {
+                        Some((pe, _)) => *pe += p,
+                        None => errors.push((p, error)),
+                    }
+                    merge(&mut next, p, s);
+                }
+            }
+        }
+        if let Some(&(_, error)) = errors.iter().max_by(|(a, _), (b, _)| a.total_cmp(b)) {
+            report.skipped.push(SkippedAction {
+                index,
+                action,
+                probability: errors.iter().map(|(p, _)| p).sum(),
+                error,
+            });
+        }
+        report.max_branches = report.max_branches.max(next.len());
+        layer = next;
+    }
+    for (_, (p, s)) in layer {
+        report.quality += p * s.quality as f64;
+        if s.progress >= s.recipe.difficulty {
+            report.completion += p;
+            report.high_quality += p * s.high_quality_probability().unwrap_or(100) as f64;
+        }
+        report.outcomes.push((p, s));
+    }
+    report
+}
+
+fn merge(layer: &mut HashMap<StateKey, (f64, Status)>, p: f64, s: Status) {
+    layer.entry(s.key()).or_insert((0.0, s)).0 += p;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected, monte_carlo, run_once, FailureReason, RunFailure, SkippedStep};
+    use crate::objective::{HighQuality, MaxQuality};
+    use crate::{data, Actions, Attributes, CastActionError, Recipe, Status};
+
+    fn status() -> Status {
+        let attr = Attributes {
+            level: 100,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: false,
+        };
+        Status::new(
+            attr,
+            Recipe::new(data::recipe_level_table(640), 100, 100, 100),
+        )
+    }
+
+    #[test]
+    fn reproducible() {
+        let s = status();
+        let actions = [
+            Actions::HastyTouch,
+            Actions::DaringTouch,
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::RapidSynthesis,
+        ];
+        let a = monte_carlo(&s, &actions, 200, 1);
+        let b = monte_carlo(&s, &actions, 200, 1);
+        for (x, y) in a.runs.iter().zip(&b.runs) {
+            assert_eq!(
+                (x.seed, x.progress, x.quality),
+                (y.seed, y.progress, y.quality)
+            );
+            let z = run_once(&s, &actions, x.seed);
+            assert_eq!((x.progress, x.quality), (z.progress, z.quality));
+        }
+        let rate = a.completion_rate();
+        assert!(rate > 0.35 && rate < 0.65, "{}", rate);
+        assert!(a.runs.iter().any(|r| matches!(
+            r.failure,
+            Some(RunFailure::DurabilityExhausted { index: 5 })
+        )));
+        assert!(a.runs.iter().any(|r| matches!(
+            r.skipped[..],
+            [SkippedStep {
+                index: 1,
+                error: CastActionError::RequireHastyTouchSuccessed,
+                ..
+            }]
+        )));
+    }
+
+    #[test]
+    fn distributions() {
+        let s = status();
+        let actions = [
+            Actions::HastyTouch,
+            Actions::DaringTouch,
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::RapidSynthesis,
+        ];
+        let report = monte_carlo(&s, &actions, 500, 3);
+        let n = report.runs.len();
+
+        let counts = report.failure_counts();
+        let failed = report.runs.iter().filter(|r| r.failure.is_some()).count();
+        assert_eq!(counts.values().sum::<usize>(), failed);
+        assert!(counts[&FailureReason::DurabilityExhausted] > 0);
+
+        let histogram = report.quality_histogram(100);
+        assert_eq!(histogram.values().sum::<usize>(), n);
+        assert!(histogram.keys().all(|k| k % 100 == 0));
+        assert_eq!(report.progress_histogram(1).values().sum::<usize>(), n);
+        assert_eq!(report.high_quality_histogram().values().sum::<usize>(), n);
+
+        let min = report.runs.iter().map(|r| r.quality).min();
+        let max = report.runs.iter().map(|r| r.quality).max();
+        assert_eq!(report.quality_percentile(0.0), min);
+        assert_eq!(report.quality_percentile(100.0), max);
+        assert!(report.quality_percentile(25.0) <= report.quality_percentile(75.0));
+        assert_eq!(report.high_quality_percentile(0.0), Some(0));
+        assert!(report.progress_percentile(50.0).is_some());
+        assert_eq!(
+            monte_carlo(&s, &actions, 0, 3).quality_percentile(50.0),
+            None
+        );
+    }
+
+    #[test]
+    fn deterministic_rotation() {
+        let s = status();
+        let actions = [
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::Groundwork,
+        ];
+        let report = monte_carlo(&s, &actions, 100, 7);
+        assert_eq!(report.completion_rate(), 1.0);
+        assert_eq!(report.mean_progress(), s.recipe.difficulty as f64);
+
+        let report = monte_carlo(&s, &actions[..2], 10, 7);
+        assert_eq!(report.completion_rate(), 0.0);
+        assert_eq!(report.mean_high_quality(), 0.0);
+        assert_eq!(report.mean_score(&MaxQuality), 0.0);
+    }
+
+    #[test]
+    fn expected_value() {
+        let s = status();
+        let actions = [
+            Actions::HastyTouch,
+            Actions::DaringTouch,
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::RapidSynthesis,
+        ];
+        let report = expected(&s, &actions);
+        // 仓促失败时跳过冒进，仍然可以继续制作
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].index, 1);
+        assert!((report.skipped[0].probability - 0.4).abs() < 1e-6);
+        assert!(matches!(
+            report.skipped[0].error,
+            CastActionError::RequireHastyTouchSuccessed
+        ));
+        assert!((report.completion - 0.5).abs() < 1e-6);
+        assert!((report.expected_score(&HighQuality) - report.high_quality).abs() < 1e-6);
+        let completion =
+            report.expected_score(&|s: &Status| (s.progress >= s.recipe.difficulty) as u32 as f64);
+        assert!((completion - report.completion).abs() < 1e-6);
+
+        let mc = monte_carlo(&s, &actions[..1], 20000, 3);
+        let report = expected(&s, &actions[..1]);
+        assert!((mc.mean_quality() - report.quality).abs() / report.quality < 0.02);
+        assert_eq!(report.completion, 0.0);
+    }
+
+    #[test]
+    fn skip_rule() {
+        // 仓促失败时无法发动冒进，两种评估都跳过它并继续执行后续技能
+        let s = status();
+        let actions = [
+            Actions::HastyTouch,
+            Actions::DaringTouch,
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::RapidSynthesis,
+        ];
+        let mc = monte_carlo(&s, &actions, 20000, 5);
+        let report = expected(&s, &actions);
+        assert!((mc.skip_rate(1) - report.skipped[0].probability).abs() < 0.02);
+        assert!((mc.completion_rate() - report.completion).abs() < 0.02);
+        assert!((mc.mean_quality() - report.quality).abs() / report.quality < 0.02);
+        assert!(mc
+            .runs
+            .iter()
+            .filter(|r| !r.skipped.is_empty())
+            .any(|r| r.failure.is_none()));
+    }
+}
@@ -13,7 +13,17 @@ use serde::{
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
+pub mod consumable;
 pub mod data;
+pub mod evaluate;
+pub mod locale;
+pub mod macros;
+pub mod objective;
+pub mod random;
+pub mod solver;
+pub mod stats;
+
+use locale::Locale;
 
 /// 代表一个玩家在作业时可以使用的一个技能的枚举。
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -103,7 +113,231 @@ impl<'de> Deserialize<'de> for Actions {
     }
 }
 
+/// 技能的分类
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum ActionCategory {
+    /// 作业类技能，推动进展
+    Synthesis,
+    /// 加工类技能，提高品质
+    Touch,
+    /// 施加buff的技能
+    Buff,
+    /// 恢复耐久的技能
+    Repair,
+    /// 其他技能
+    Other,
+}
+
+/// 技能在某个玩家等级下的静态数据，不考虑buff、球色与连击的影响。
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct ActionInfo {
+    /// 技能分类
+    pub category: ActionCategory,
+    /// 计入特性加成后的作业效率（百分比），不推动进展的技能为0
+    pub progress_efficiency: u16,
+    /// 计入特性加成后的加工效率（百分比），不提高品质的技能为0。
+    /// 比尔格的祝福的效率还会随内静层数增加，这里给出的是0层时的效率
+    pub quality_efficiency: u16,
+    /// 基础耐久消耗
+    pub durability: u16,
+    /// 基础CP消耗，不考虑连击
+    pub craft_points: i32,
+    /// 在宏中发动后需要等待的秒数
+    pub wait_time: u8,
+    /// 习得等级
+    pub unlock_level: u8,
+    /// 是否为用于模拟失败结果的假技能
+    pub fake: bool,
+}
+
 impl Actions {
+    const ALL: [Actions; 40] = [
+        Actions::BasicSynthesis,
+        Actions::BasicTouch,
+        Actions::MastersMend,
+        Actions::HastyTouch,
+        Actions::RapidSynthesis,
+        Actions::Observe,
+        Actions::TricksOfTheTrade,
+        Actions::WasteNot,
+        Actions::Veneration,
+        Actions::StandardTouch,
+        Actions::GreatStrides,
+        Actions::Innovation,
+        Actions::FinalAppraisal,
+        Actions::WasteNotII,
+        Actions::ByregotsBlessing,
+        Actions::PreciseTouch,
+        Actions::MuscleMemory,
+        Actions::CarefulSynthesis,
+        Actions::Manipulation,
+        Actions::PrudentTouch,
+        Actions::AdvancedTouch,
+        Actions::Reflect,
+        Actions::PreparatoryTouch,
+        Actions::Groundwork,
+        Actions::DelicateSynthesis,
+        Actions::IntensiveSynthesis,
+        Actions::TrainedEye,
+        Actions::PrudentSynthesis,
+        Actions::TrainedFinesse,
+        Actions::CarefulObservation,
+        Actions::HeartAndSoul,
+        Actions::RefinedTouch,
+        Actions::DaringTouch,
+        Actions::QuickInnovation,
+        Actions::ImmaculateMend,
+        Actions::TrainedPerfection,
+        Actions::StellarSteadyHand,
+        Actions::RapidSynthesisFail,
+        Actions::HastyTouchFail,
+        Actions::DaringTouchFail,
+    ];
+
+    /// 全部技能，包括用于模拟失败结果的假技能
+    pub fn all() -> &'static [Actions] {
+        &Self::ALL
+    }
+
+    /// 技能的分类
+    pub fn category(&self) -> ActionCategory {
+        match self {
+            Actions::BasicSynthesis
+            | Actions::RapidSynthesis
+            | Actions::MuscleMemory
+            | Actions::CarefulSynthesis
+            | Actions::Groundwork
+            | Actions::DelicateSynthesis
+            | Actions::IntensiveSynthesis
+            | Actions::PrudentSynthesis
+            | Actions::RapidSynthesisFail => ActionCategory::Synthesis,
+            Actions::BasicTouch
+            | Actions::HastyTouch
+            | Actions::StandardTouch
+            | Actions::ByregotsBlessing
+            | Actions::PreciseTouch
+            | Actions::PrudentTouch
+            | Actions::AdvancedTouch
+            | Actions::Reflect
+            | Actions::PreparatoryTouch
+            | Actions::TrainedEye
+            | Actions::TrainedFinesse
+            | Actions::RefinedTouch
+            | Actions::DaringTouch
+            | Actions::HastyTouchFail
+            | Actions::DaringTouchFail => ActionCategory::Touch,
+            Actions::WasteNot
+            | Actions::Veneration
+            | Actions::GreatStrides
+            | Actions::Innovation
+            | Actions::FinalAppraisal
+            | Actions::WasteNotII
+            | Actions::Manipulation
+            | Actions::HeartAndSoul
+            | Actions::QuickInnovation
+            | Actions::TrainedPerfection
+            | Actions::StellarSteadyHand => ActionCategory::Buff,
+            Actions::MastersMend | Actions::ImmaculateMend => ActionCategory::Repair,
+            Actions::Observe | Actions::TricksOfTheTrade | Actions::CarefulObservation => {
+                ActionCategory::Other
+            }
+        }
+    }
+
+    /// 在宏中发动后需要等待的秒数：有动画的作业、加工与修理技能为3秒，其余为2秒
+    pub fn wait_time(&self) -> u8 {
+        match self.category() {
+            ActionCategory::Synthesis | ActionCategory::Touch | ActionCategory::Repair => 3,
+            ActionCategory::Buff | ActionCategory::Other => 2,
+        }
+    }
+
+    /// 技能在指定玩家等级下的静态数据
+    ///
+    /// Example:
+    /// ```rust
+    /// use ffxiv_crafting::Actions;
+    ///
+    /// let info = Actions::Groundwork.info(90);
+    /// assert_eq!(info.progress_efficiency, 360);
+    /// assert_eq!(info.durability, 20);
+    /// assert_eq!(info.craft_points, 18);
+    /// assert_eq!(Actions::Groundwork.info(80).progress_efficiency, 300);
+    /// ```
+    pub fn info(&self, level: u8) -> ActionInfo {
+        // (作业效率, 加工效率, 耐久消耗, CP消耗)
+        let (progress, quality, durability, craft_points) = match self {
+            Actions::BasicSynthesis => (if level < 31 { 100 } else { 120 }, 0, 10, 0),
+            Actions::BasicTouch => (0, 100, 10, 18),
+            Actions::MastersMend => (0, 0, 0, 88),
+            Actions::HastyTouch => (0, 100, 10, 0),
+            Actions::RapidSynthesis => (if level < 63 { 250 } else { 500 }, 0, 10, 0),
+            Actions::Observe => (0, 0, 0, 7),
+            Actions::TricksOfTheTrade => (0, 0, 0, 0),
+            Actions::WasteNot => (0, 0, 0, 56),
+            Actions::Veneration => (0, 0, 0, 18),
+            Actions::StandardTouch => (0, 125, 10, 32),
+            Actions::GreatStrides => (0, 0, 0, 32),
+            Actions::Innovation => (0, 0, 0, 18),
+            Actions::FinalAppraisal => (0, 0, 0, 1),
+            Actions::WasteNotII => (0, 0, 0, 98),
+            Actions::ByregotsBlessing => (0, 100, 10, 24),
+            Actions::PreciseTouch => (0, 150, 10, 18),
+            Actions::MuscleMemory => (300, 0, 10, 6),
+            Actions::CarefulSynthesis => (if level < 82 { 150 } else { 180 }, 0, 10, 7),
+            Actions::Manipulation => (0, 0, 0, 96),
+            Actions::PrudentTouch => (0, 100, 5, 25),
+            Actions::AdvancedTouch => (0, 150, 10, 46),
+            Actions::Reflect => (0, 300, 10, 6),
+            Actions::PreparatoryTouch => (0, 200, 20, 40),
+            Actions::Groundwork => (if level < 86 { 300 } else { 360 }, 0, 20, 18),
+            Actions::DelicateSynthesis => (if level < 94 { 100 } else { 150 }, 100, 10, 32),
+            Actions::IntensiveSynthesis => (400, 0, 10, 6),
+            Actions::TrainedEye => (0, 0, 0, 250),
+            Actions::PrudentSynthesis => (180, 0, 5, 18),
+            Actions::TrainedFinesse => (0, 100, 0, 32),
+            Actions::CarefulObservation => (0, 0, 0, 0),
+            Actions::HeartAndSoul => (0, 0, 0, 0),
+            // 7.0
+            Actions::RefinedTouch => (0, 100, 10, 24),
+            Actions::DaringTouch => (0, 150, 10, 0),
+            Actions::QuickInnovation => (0, 0, 0, 0),
+            Actions::ImmaculateMend => (0, 0, 0, 112),
+            Actions::TrainedPerfection => (0, 0, 0, 0),
+            // 7.4
+            Actions::StellarSteadyHand => (0, 0, 0, 0),
+            // fake actions
+            Actions::RapidSynthesisFail => (0, 0, 10, 0),
+            Actions::HastyTouchFail => (0, 0, 10, 0),
+            Actions::DaringTouchFail => (0, 0, 10, 0),
+        };
+        ActionInfo {
+            category: self.category(),
+            progress_efficiency: progress,
+            quality_efficiency: quality,
+            durability,
+            craft_points,
+            wait_time: self.wait_time(),
+            unlock_level: self.unlock_level(),
+            fake: matches!(
+                self,
+                Actions::RapidSynthesisFail | Actions::HastyTouchFail | Actions::DaringTouchFail
+            ),
+        }
+    }
+
+    /// 对于有成功率的技能，返回其失败时对应的假技能
+    pub fn fail_action(&self) -> Option<Actions> {
+        match self {
+            Actions::RapidSynthesis => Some(Actions::RapidSynthesisFail),
+            Actions::HastyTouch => Some(Actions::HastyTouchFail),
+            Actions::DaringTouch => Some(Actions::DaringTouchFail),
+            _ => None,
+        }
+    }
+
     fn unlock_level(&self) -> u8 {
         match self {
             Actions::BasicSynthesis => 1,
@@ -208,50 +442,50 @@ impl TryFrom<&str> for Actions {
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         Ok(match value {
-            "basic_synthesis" | "制作" => Actions::BasicSynthesis,
-            "basic_touch" | "加工" => Actions::BasicTouch,
-            "masters_mend" | "精修" => Actions::MastersMend,
-            "hasty_touch" | "仓促" => Actions::HastyTouch,
-            "rapid_synthesis" | "高速制作" => Actions::RapidSynthesis,
-            "observe" | "观察" => Actions::Observe,
-            "tricks_of_the_trade" | "秘诀" => Actions::TricksOfTheTrade,
-            "waste_not" | "俭约" => Actions::WasteNot,
-            "veneration" | "崇敬" => Actions::Veneration,
-            "standard_touch" | "中级加工" => Actions::StandardTouch,
-            "great_strides" | "阔步" => Actions::GreatStrides,
-            "innovation" | "改革" => Actions::Innovation,
-            "final_appraisal" | "最终确认" => Actions::FinalAppraisal,
-            "waste_not_ii" | "长期俭约" => Actions::WasteNotII,
-            "byregot_s_blessing" | "比尔格的祝福" => Actions::ByregotsBlessing,
-            "precise_touch" | "集中加工" => Actions::PreciseTouch,
-            "muscle_memory" | "坚信" => Actions::MuscleMemory,
-            "careful_synthesis" | "模范制作" => Actions::CarefulSynthesis,
-            "manipulation" | "掌握" => Actions::Manipulation,
-            "prudent_touch" | "俭约加工" => Actions::PrudentTouch,
-            "advanced_touch" | "上级加工" => Actions::AdvancedTouch,
-            "reflect" | "闲静" => Actions::Reflect,
-            "preparatory_touch" | "坯料加工" => Actions::PreparatoryTouch,
-            "groundwork" | "坯料制作" => Actions::Groundwork,
-            "delicate_synthesis" | "精密制作" => Actions::DelicateSynthesis,
-            "intensive_synthesis" | "集中制作" => Actions::IntensiveSynthesis,
-            "trained_eye" | "工匠的神速技巧" => Actions::TrainedEye,
-            "prudent_synthesis" | "俭约制作" => Actions::PrudentSynthesis,
-            "trained_finesse" | "工匠的神技" => Actions::TrainedFinesse,
-            "careful_observation" | "设计变动" => Actions::CarefulObservation,
-            "heart_and_soul" | "专心致志" => Actions::HeartAndSoul,
+            "basic_synthesis" => Actions::BasicSynthesis,
+            "basic_touch" => Actions::BasicTouch,
+            "masters_mend" => Actions::MastersMend,
+            "hasty_touch" => Actions::HastyTouch,
+            "rapid_synthesis" => Actions::RapidSynthesis,
+            "observe" => Actions::Observe,
+            "tricks_of_the_trade" => Actions::TricksOfTheTrade,
+            "waste_not" => Actions::WasteNot,
+            "veneration" => Actions::Veneration,
+            "standard_touch" => Actions::StandardTouch,
+            "great_strides" => Actions::GreatStrides,
+            "innovation" => Actions::Innovation,
+            "final_appraisal" => Actions::FinalAppraisal,
+            "waste_not_ii" => Actions::WasteNotII,
+            "byregot_s_blessing" => Actions::ByregotsBlessing,
+            "precise_touch" => Actions::PreciseTouch,
+            "muscle_memory" => Actions::MuscleMemory,
+            "careful_synthesis" => Actions::CarefulSynthesis,
+            "manipulation" => Actions::Manipulation,
+            "prudent_touch" => Actions::PrudentTouch,
+            "advanced_touch" => Actions::AdvancedTouch,
+            "reflect" => Actions::Reflect,
+            "preparatory_touch" => Actions::PreparatoryTouch,
+            "groundwork" => Actions::Groundwork,
+            "delicate_synthesis" => Actions::DelicateSynthesis,
+            "intensive_synthesis" => Actions::IntensiveSynthesis,
+            "trained_eye" => Actions::TrainedEye,
+            "prudent_synthesis" => Actions::PrudentSynthesis,
+            "trained_finesse" => Actions::TrainedFinesse,
+            "careful_observation" => Actions::CarefulObservation,
+            "heart_and_soul" => Actions::HeartAndSoul,
             // 7.0
-            "refined_touch" | "精炼加工" => Actions::RefinedTouch,
-            "daring_touch" | "冒进" => Actions::DaringTouch,
-            "quick_innovation" | "快速改革" => Actions::QuickInnovation,
-            "immaculate_mend" | "巧夺天工" => Actions::ImmaculateMend,
-            "trained_perfection" | "工匠的绝技" => Actions::TrainedPerfection,
+            "refined_touch" => Actions::RefinedTouch,
+            "daring_touch" => Actions::DaringTouch,
+            "quick_innovation" => Actions::QuickInnovation,
+            "immaculate_mend" => Actions::ImmaculateMend,
+            "trained_perfection" => Actions::TrainedPerfection,
             // 7.4
-            "stellar_steady_hand" | "宇宙稳手" => Actions::StellarSteadyHand,
+            "stellar_steady_hand" => Actions::StellarSteadyHand,
             // fake actions
             "rapid_synthesis_fail" => Actions::RapidSynthesisFail,
             "hasty_touch_fail" => Actions::HastyTouchFail,
             "daring_touch_fail" => Actions::DaringTouchFail,
-            _ => return Err(UnknownSkillErr),
+            _ => return locale::find_action(value).ok_or(UnknownSkillErr),
         })
     }
 }
@@ -276,7 +510,7 @@ impl de::Error for UnknownSkillErr {
 
 /// 代表了当前的“制作状态”，也就是俗称的球色。
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum Condition {
     /// 白：通常
     Normal,
@@ -322,7 +556,7 @@ impl From<&Condition> for &str {
 }
 
 impl TryFrom<&str> for Condition {
-    type Error = ();
+    type Error = UnknownConditionErr;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         Ok(match value {
@@ -337,11 +571,22 @@ impl TryFrom<&str> for Condition {
             "primed" => Condition::Primed,
             "good_omen" => Condition::GoodOmen,
             "robust" => Condition::Robust,
-            _ => return Err(()),
+            _ => return locale::find_condition(value).ok_or(UnknownConditionErr),
         })
     }
 }
 
+#[derive(Debug)]
+pub struct UnknownConditionErr;
+
+impl Error for UnknownConditionErr {}
+
+impl Display for UnknownConditionErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown condition name")
+    }
+}
+
 impl Condition {
     fn touch_ratio(&self) -> f32 {
         match self {
@@ -360,9 +605,11 @@ impl Condition {
     }
 }
 
-/// 玩家装备属性
+/// 玩家装备属性。
+///
+/// 使用 [`Attributes::new`] 或 [`Default`] 构造可以不受之后新增字段的影响。
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
 pub struct Attributes {
     /// 玩家等级
     pub level: u8,
@@ -372,6 +619,56 @@ pub struct Attributes {
     pub control: i32,
     /// 制作力
     pub craft_points: i32,
+    /// 是否为该职业的专家，只有专家才能使用设计变动与专心致志。
+    /// 属性中应当已经包含专家之证的加成，参见 [`Attributes::with_specialist`]
+    #[cfg_attr(feature = "serde-support", serde(default))]
+    pub specialist: bool,
+}
+
+impl Attributes {
+    /// 专家之证提供的作业精度、加工精度与制作力加成
+    pub const SPECIALIST_BONUS: (i32, i32, i32) = (20, 20, 15);
+
+    /// 非专家玩家的属性
+    pub const fn new(level: u8, craftsmanship: i32, control: i32, craft_points: i32) -> Self {
+        Self {
+            level,
+            craftsmanship,
+            control,
+            craft_points,
+            specialist: false,
+        }
+    }
+
+    /// 装备专家之证后的属性：成为专家并获得 [`Attributes::SPECIALIST_BONUS`]，已经是专家时不变
+    pub fn with_specialist(self) -> Self {
+        if self.specialist {
+            return self;
+        }
+        let (craftsmanship, control, craft_points) = Self::SPECIALIST_BONUS;
+        Self {
+            craftsmanship: self.craftsmanship + craftsmanship,
+            control: self.control + control,
+            craft_points: self.craft_points + craft_points,
+            specialist: true,
+            ..self
+        }
+    }
+
+    /// 卸下专家之证后的属性，不是专家时不变
+    pub fn without_specialist(self) -> Self {
+        if !self.specialist {
+            return self;
+        }
+        let (craftsmanship, control, craft_points) = Self::SPECIALIST_BONUS;
+        Self {
+            craftsmanship: self.craftsmanship - craftsmanship,
+            control: self.control - control,
+            craft_points: self.craft_points - craft_points,
+            specialist: false,
+            ..self
+        }
+    }
 }
 
 /// 储存了一次制作中配方的信息。
@@ -456,7 +753,7 @@ impl Recipe {
 
 /// Buffs 储存了一次制作中玩家全部buff状态信息
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, Hash, Eq, PartialEq)]
 pub struct Buffs {
     /// 坚信
     pub muscle_memory: u8,
@@ -486,11 +783,75 @@ pub struct Buffs {
     pub stellar_steady_hand: u8,
 }
 
+/// 用于指代 [`Buffs`] 中的某一项buff
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Buff {
+    MuscleMemory,
+    GreatStrides,
+    Veneration,
+    Innovation,
+    InnerQuiet,
+    FinalAppraisal,
+    Manipulation,
+    WasteNot,
+    WasteNotII,
+    Expedience,
+    HeartAndSoul,
+    TrainedPerfection,
+    StellarSteadyHand,
+}
+
+impl Buff {
+    pub const ALL: [Buff; 13] = [
+        Buff::MuscleMemory,
+        Buff::GreatStrides,
+        Buff::Veneration,
+        Buff::Innovation,
+        Buff::InnerQuiet,
+        Buff::FinalAppraisal,
+        Buff::Manipulation,
+        Buff::WasteNot,
+        Buff::WasteNotII,
+        Buff::Expedience,
+        Buff::HeartAndSoul,
+        Buff::TrainedPerfection,
+        Buff::StellarSteadyHand,
+    ];
+}
+
 fn round_down(v: f64, scale: f64) -> f64 {
     (v * scale).floor() / scale
 }
 
 impl Buffs {
+    /// 获取某项buff的剩余回合数，对于内静则是其层数
+    pub fn get(&self, buff: Buff) -> u8 {
+        match buff {
+            Buff::MuscleMemory => self.muscle_memory,
+            Buff::GreatStrides => self.great_strides,
+            Buff::Veneration => self.veneration,
+            Buff::Innovation => self.innovation,
+            Buff::InnerQuiet => self.inner_quiet,
+            Buff::FinalAppraisal => self.final_appraisal,
+            Buff::Manipulation => self.manipulation,
+            Buff::WasteNot => self.wast_not,
+            Buff::WasteNotII => self.wast_not_ii,
+            Buff::Expedience => self.expedience,
+            Buff::HeartAndSoul => self.heart_and_soul,
+            Buff::TrainedPerfection => self.trained_perfection,
+            Buff::StellarSteadyHand => self.stellar_steady_hand,
+        }
+    }
+
+    /// 遍历当前生效中的buff
+    pub fn active(&self) -> impl Iterator<Item = (Buff, u8)> + '_ {
+        Buff::ALL
+            .iter()
+            .map(|&b| (b, self.get(b)))
+            .filter(|&(_, v)| v > 0)
+    }
+
     pub(crate) fn synthesis(&self, skill_e: f64) -> f64 {
         let mut e = 0.0;
         if self.muscle_memory > 0 {
@@ -541,7 +902,7 @@ impl Buffs {
 }
 
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum ComboStates {
     Observed = 1,
     BasicTouched,
@@ -549,7 +910,7 @@ pub enum ComboStates {
 }
 
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
 pub struct ActionLimits {
     /// 设计变动使用次数
     pub careful_observation_used: u8,
@@ -595,6 +956,21 @@ pub struct Status {
     pub condition: Condition,
 }
 
+/// 一次制作过程中会发生变化的全部状态，用于比较与合并[`Status`]。
+/// 不包含玩家属性、配方与预计算数据，因此只能用于比较同一次制作中的状态。
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub(crate) struct StateKey {
+    buffs: Buffs,
+    combo: Option<ComboStates>,
+    limits: ActionLimits,
+    durability: u16,
+    craft_points: i32,
+    progress: u16,
+    quality: u32,
+    step: i32,
+    condition: Condition,
+}
+
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct Caches {
@@ -629,7 +1005,7 @@ impl Caches {
 
 /// 技能释放错误
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum CastActionError {
     /// 耐久不足
     DurabilityNotEnough,
@@ -651,6 +1027,8 @@ pub enum CastActionError {
     RequireInnerQuiet1,
     /// 该技能只有在内静的档数为10时才可以使用
     RequireInnerQuiet10,
+    /// 该技能只有专家才能使用
+    RequireSpecialist,
     /// 设计变动最多使用三次
     CarefulObservationUsed3,
     /// 专心致志一次制作只能使用一次
@@ -669,30 +1047,108 @@ pub enum CastActionError {
 
 impl Display for CastActionError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            CastActionError::DurabilityNotEnough => "durability not enough",
-            CastActionError::CraftPointNotEnough => "craft point not enough",
-            CastActionError::CraftingAlreadyFinished => "crafting already finished",
-            CastActionError::PlayerLevelTooLow => "player level too low",
-            CastActionError::RequireGoodOrExcellent => "require good or excellent",
-            CastActionError::NotAllowedInWastNotBuff => "not allowed in wast not buff",
-            CastActionError::OnlyAllowedInFirstStep => "only allowed in first step",
-            CastActionError::LevelGapMustGreaterThanTen => "level gap must greater than 10",
-            CastActionError::RequireInnerQuiet1 => "require at least 1 stack of inner quiet",
-            CastActionError::RequireInnerQuiet10 => "require 10 stack of inner quiet",
-            CastActionError::CarefulObservationUsed3 => "careful observation can only use 3 times",
-            CastActionError::HeartAndSoulUsed => "heart and soul can be only used once",
-            CastActionError::RequireHastyTouchSuccessed => "require hasty touch successed first",
-            CastActionError::QuickInnovationUsed => "quick innovation can be only used once",
-            CastActionError::NotAllowedInInnovationBuff => "not allowed in innovation buff",
-            CastActionError::TrainedPerfectionUsed => "trained perfection can be only used once",
-            CastActionError::StellarSteadyHandUsed => "stellar steady hand can be only used once",
-        })
+        f.write_str(self.message(Locale::En))
     }
 }
 
 impl Error for CastActionError {}
 
+/// 发动一次技能所造成的各项变化
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CastOutcome {
+    /// 增加的进展
+    pub progress: u16,
+    /// 增加的品质
+    pub quality: u32,
+    /// 技能消耗的耐久
+    pub durability_lost: u16,
+    /// 技能本身恢复的耐久，例如精修
+    pub durability_repaired: u16,
+    /// 掌握效果恢复的耐久
+    pub manipulation_repaired: u16,
+    /// 消耗的CP
+    pub craft_points_spent: i32,
+    /// 恢复的CP，例如秘诀
+    pub craft_points_restored: i32,
+    /// 内静层数的变化
+    pub inner_quiet: i8,
+    /// 新获得的buff
+    pub buffs_applied: Vec<Buff>,
+    /// 被刷新持续时间的buff
+    pub buffs_refreshed: Vec<Buff>,
+    /// 被消耗或到期消失的buff
+    pub buffs_expired: Vec<Buff>,
+    /// 是否触发了最终确认
+    pub final_appraisal_triggered: bool,
+}
+
+/// 模拟中的一步
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct SimulatedStep {
+    pub action: Actions,
+    /// 该技能造成的各项变化
+    pub outcome: CastOutcome,
+    /// 发动该技能后的状态
+    pub status: Status,
+}
+
+/// 技能序列中第一个无法发动的技能
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IllegalStep {
+    /// 该技能在技能序列中的位置
+    pub index: usize,
+    pub action: Actions,
+    pub error: CastActionError,
+}
+
+/// [`Status::simulate`] 的结果
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct SimulationReport {
+    /// 成功发动的每一步
+    pub steps: Vec<SimulatedStep>,
+    /// 第一个无法发动的技能，模拟在此处停止
+    pub illegal: Option<IllegalStep>,
+    /// 制作在技能序列结束前已经结束时，剩余未发动的技能数
+    pub unused: usize,
+    /// 是否推满了进展
+    pub completed: bool,
+    /// 最终进展
+    pub progress: u16,
+    /// 最终品质
+    pub quality: u32,
+    /// 制作完成时的HQ概率（百分数），未完成时为0
+    pub high_quality: i32,
+}
+
+impl SimulationReport {
+    /// 制作是否在技能序列结束前就已经结束，即有剩余未发动的技能
+    pub fn finished_early(&self) -> bool {
+        self.unused > 0
+    }
+}
+
+/// 发动技能过程中无法事后从状态差异还原的信息
+struct CastTrace {
+    /// 技能效果结算后、掌握恢复前的耐久
+    durability: u16,
+    manipulation_repaired: u16,
+    final_appraisal_triggered: bool,
+}
+
+impl CastTrace {
+    fn new(s: &Status) -> Self {
+        Self {
+            durability: s.durability,
+            manipulation_repaired: 0,
+            final_appraisal_triggered: false,
+        }
+    }
+}
+
 impl Status {
     pub fn new(attributes: Attributes, recipe: Recipe) -> Self {
         Status {
@@ -786,62 +1242,13 @@ impl Status {
     /// 计算当前状态指定技能消耗的CP。
     /// 考虑连击与球色
     pub fn craft_point(&self, skill: Actions) -> i32 {
-        let cp: i32 = match skill {
-            Actions::BasicSynthesis => 0,
-            Actions::BasicTouch => 18,
-            Actions::MastersMend => 88,
-            Actions::HastyTouch => 0,
-            Actions::RapidSynthesis => 0,
-            Actions::Observe => 7,
-            Actions::TricksOfTheTrade => 0,
-            Actions::WasteNot => 56,
-            Actions::Veneration => 18,
-            Actions::StandardTouch => {
-                if let Some(ComboStates::BasicTouched) = self.combo {
-                    18
-                } else {
-                    32
-                }
-            }
-            Actions::GreatStrides => 32,
-            Actions::Innovation => 18,
-            Actions::FinalAppraisal => 1,
-            Actions::WasteNotII => 98,
-            Actions::ByregotsBlessing => 24,
-            Actions::PreciseTouch => 18,
-            Actions::MuscleMemory => 6,
-            Actions::CarefulSynthesis => 7,
-            Actions::Manipulation => 96,
-            Actions::PrudentTouch => 25,
-            Actions::AdvancedTouch => {
-                if let Some(ComboStates::StandardTouched | ComboStates::Observed) = self.combo {
-                    18
-                } else {
-                    46
-                }
-            }
-            Actions::Reflect => 6,
-            Actions::PreparatoryTouch => 40,
-            Actions::Groundwork => 18,
-            Actions::DelicateSynthesis => 32,
-            Actions::IntensiveSynthesis => 6,
-            Actions::TrainedEye => 250,
-            Actions::PrudentSynthesis => 18,
-            Actions::TrainedFinesse => 32,
-            Actions::CarefulObservation => 0,
-            Actions::HeartAndSoul => 0,
-            // 7.0
-            Actions::RefinedTouch => 24,
-            Actions::DaringTouch => 0,
-            Actions::ImmaculateMend => 112,
-            Actions::QuickInnovation => 0,
-            Actions::TrainedPerfection => 0,
-            // 7.4
-            Actions::StellarSteadyHand => 0,
-            // fake actions
-            Actions::RapidSynthesisFail => 0,
-            Actions::HastyTouchFail => 0,
-            Actions::DaringTouchFail => 0,
+        let cp = match (skill, self.combo) {
+            (Actions::StandardTouch, Some(ComboStates::BasicTouched)) => 18,
+            (
+                Actions::AdvancedTouch,
+                Some(ComboStates::StandardTouched | ComboStates::Observed),
+            ) => 18,
+            _ => skill.info(self.attributes.level).craft_points,
         };
         if let Condition::Pliant = self.condition {
             cp - cp / 2
@@ -851,70 +1258,224 @@ impl Status {
     }
 
     /// 发动一次技能。
+    ///
+    /// 该方法不检查当前状态是否允许发动该技能，需要检查时请使用 [`Status::try_cast_action`]。
     pub fn cast_action(&mut self, action: Actions) {
-        self.craft_points -= self.craft_point(action);
-        let mut next_combo_state = None;
-        match action {
-            Actions::BasicSynthesis => {
-                self.cast_synthesis(10, if self.attributes.level < 31 { 1.0 } else { 1.2 })
+        self.cast(action);
+    }
+
+    /// 发动一次技能，并返回该技能造成的各项变化。
+    ///
+    /// Example:
+    /// ```rust
+    /// use ffxiv_crafting::{data, Actions, Attributes, Buff, Recipe, Status};
+    ///
+    /// let attr = Attributes {
+    ///     level: 90,
+    ///     craftsmanship: 3258,
+    ///     control: 3340,
+    ///     craft_points: 654,
+    ///     specialist: false,
+    /// };
+    /// let recipe = Recipe::new(data::recipe_level_table(590), 100, 100, 100);
+    /// let mut s = Status::new(attr, recipe);
+    /// let outcome = s.cast_action_with_outcome(Actions::MuscleMemory);
+    /// assert_eq!(outcome.progress, s.progress);
+    /// assert_eq!(outcome.craft_points_spent, 6);
+    /// assert_eq!(outcome.buffs_applied, [Buff::MuscleMemory]);
+    /// ```
+    pub fn cast_action_with_outcome(&mut self, action: Actions) -> CastOutcome {
+        let (progress, quality, durability, craft_points, buffs, step) = (
+            self.progress,
+            self.quality,
+            self.durability,
+            self.craft_points,
+            self.buffs,
+            self.step,
+        );
+        let craft_points_spent = self.craft_point(action);
+        let trace = self.cast(action);
+
+        // 最终确认、设计变动等技能不消耗工次，buff的持续时间也不会减少
+        let mut ticked = buffs;
+        if self.step != step {
+            ticked.next();
+        }
+        let mut outcome = CastOutcome {
+            progress: self.progress - progress,
+            quality: self.quality - quality,
+            durability_lost: durability.saturating_sub(trace.durability),
+            durability_repaired: trace.durability.saturating_sub(durability),
+            manipulation_repaired: trace.manipulation_repaired,
+            craft_points_spent,
+            craft_points_restored: self.craft_points - (craft_points - craft_points_spent),
+            inner_quiet: self.buffs.inner_quiet as i8 - buffs.inner_quiet as i8,
+            final_appraisal_triggered: trace.final_appraisal_triggered,
+            ..Default::default()
+        };
+        for buff in Buff::ALL {
+            if buff == Buff::InnerQuiet {
+                continue;
             }
-            Actions::RapidSynthesis => {
-                self.cast_synthesis(10, if self.attributes.level < 63 { 2.5 } else { 5.0 })
+            let (before, after) = (buffs.get(buff), self.buffs.get(buff));
+            if before == 0 && after > 0 {
+                outcome.buffs_applied.push(buff);
+            } else if before > 0 && after > ticked.get(buff) {
+                outcome.buffs_refreshed.push(buff);
+            } else if before > 0 && after == 0 {
+                outcome.buffs_expired.push(buff);
             }
-            Actions::CarefulSynthesis => {
-                self.cast_synthesis(10, if self.attributes.level < 82 { 1.5 } else { 1.8 })
+        }
+        outcome
+    }
+
+    /// 检查并发动一次技能。
+    /// 若当前状态不允许发动该技能，则返回错误且不改变当前状态。
+    pub fn try_cast_action(&mut self, action: Actions) -> Result<CastOutcome, CastActionError> {
+        self.is_action_allowed(action)?;
+        Ok(self.cast_action_with_outcome(action))
+    }
+
+    /// 计算发动一次技能后的状态，不改变当前状态。
+    pub fn after(&self, action: Actions) -> Result<Status, CastActionError> {
+        self.is_action_allowed(action)?;
+        let mut s = self.clone();
+        s.cast(action);
+        Ok(s)
+    }
+
+    /// 从当前状态开始依次发动`actions`，不改变当前状态，球色保持不变。
+    ///
+    /// 模拟在遇到第一个无法发动的技能，或制作结束（进展推满或耐久耗尽）时停止，
+    /// 报告中记录每一步的状态、停止的原因以及最终的进展、品质与HQ概率。
+    ///
+    /// Example:
+    /// ```rust
+    /// use ffxiv_crafting::{data, Actions, Attributes, CastActionError, Recipe, Status};
+    ///
+    /// let attr = Attributes {
+    ///     level: 90,
+    ///     craftsmanship: 4048,
+    ///     control: 4005,
+    ///     craft_points: 594,
+    ///     specialist: false,
+    /// };
+    /// let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+    /// let s = Status::new(attr, recipe);
+    /// let report = s.simulate(&[Actions::BasicTouch, Actions::MuscleMemory]);
+    /// assert_eq!(report.steps.len(), 1);
+    /// let illegal = report.illegal.unwrap();
+    /// assert_eq!(illegal.index, 1);
+    /// assert_eq!(illegal.error, CastActionError::OnlyAllowedInFirstStep);
+    /// assert_eq!(report.quality, report.steps[0].status.quality);
+    /// ```
+    pub fn simulate(&self, actions: &[Actions]) -> SimulationReport {
+        let mut s = self.clone();
+        let mut steps = Vec::with_capacity(actions.len());
+        let mut illegal = None;
+        let mut unused = 0;
+        for (index, &action) in actions.iter().enumerate() {
+            if s.is_finished() {
+                unused = actions.len() - index;
+                break;
             }
+            match s.try_cast_action(action) {
+                Ok(outcome) => steps.push(SimulatedStep {
+                    action,
+                    outcome,
+                    status: s.clone(),
+                }),
+                Err(error) => {
+                    illegal = Some(IllegalStep {
+                        index,
+                        action,
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+        let completed = s.progress >= s.recipe.difficulty;
+        SimulationReport {
+            steps,
+            illegal,
+            unused,
+            completed,
+            progress: s.progress,
+            quality: s.quality,
+            high_quality: if completed {
+                s.high_quality_probability().unwrap_or(100)
+            } else {
+                0
+            },
+        }
+    }
+
+    fn cast(&mut self, action: Actions) -> CastTrace {
+        let info = action.info(self.attributes.level);
+        let (durability, progress_e, quality_e) = (
+            info.durability,
+            info.progress_efficiency as f64 / 100.0,
+            info.quality_efficiency as f64 / 100.0,
+        );
+        let final_appraisal = self.buffs.final_appraisal;
+        self.craft_points -= self.craft_point(action);
+        let mut next_combo_state = None;
+        match action {
+            Actions::BasicSynthesis
+            | Actions::RapidSynthesis
+            | Actions::CarefulSynthesis
+            | Actions::PrudentSynthesis => self.cast_synthesis(durability, progress_e),
             Actions::Groundwork => {
-                let mut e = if self.attributes.level < 86 { 3.0 } else { 3.6 };
-                let d = self.calc_durability(20);
+                let mut e = progress_e;
+                let d = self.calc_durability(durability);
                 if self.durability < d {
                     e *= 0.5
                 };
-                self.cast_synthesis(20, e)
+                self.cast_synthesis(durability, e)
             }
             Actions::IntensiveSynthesis => {
-                self.cast_synthesis(10, 4.0);
+                self.cast_synthesis(durability, progress_e);
                 if !matches!(self.condition, Condition::Good | Condition::Excellent) {
                     self.buffs.heart_and_soul = 0;
                 }
             }
-            Actions::PrudentSynthesis => self.cast_synthesis(5, 1.8),
 
             Actions::DelicateSynthesis => {
-                self.cast_synthesis(0, if self.attributes.level < 94 { 1.0 } else { 1.5 });
-                self.cast_touch(10, 1.0, 1);
+                self.cast_synthesis(0, progress_e);
+                self.cast_touch(durability, quality_e, 1);
             }
 
             Actions::BasicTouch => {
-                self.cast_touch(10, 1.0, 1);
+                self.cast_touch(durability, quality_e, 1);
                 next_combo_state = Some(ComboStates::BasicTouched);
             }
             Actions::HastyTouch => {
-                self.cast_touch(10, 1.0, 1);
+                self.cast_touch(durability, quality_e, 1);
                 self.buffs.expedience = 2;
             }
             Actions::StandardTouch => {
                 if let Some(ComboStates::BasicTouched) = self.combo {
                     next_combo_state = Some(ComboStates::StandardTouched);
                 };
-                self.cast_touch(10, 1.25, 1)
+                self.cast_touch(durability, quality_e, 1)
             }
-            Actions::AdvancedTouch => self.cast_touch(10, 1.5, 1),
+            Actions::AdvancedTouch => self.cast_touch(durability, quality_e, 1),
             Actions::ByregotsBlessing => {
-                let e = (1.0 + self.buffs.inner_quiet as f64 * 0.2).min(3.0);
-                self.cast_touch(10, e, -(self.buffs.inner_quiet as i8));
+                let e = (quality_e + self.buffs.inner_quiet as f64 * 0.2).min(3.0);
+                self.cast_touch(durability, e, -(self.buffs.inner_quiet as i8));
             }
             Actions::PreciseTouch => {
-                self.cast_touch(10, 1.5, 2);
+                self.cast_touch(durability, quality_e, 2);
                 if !matches!(self.condition, Condition::Good | Condition::Excellent)
                     && self.buffs.heart_and_soul > 0
                 {
                     self.buffs.heart_and_soul = 0;
                 }
             }
-            Actions::PrudentTouch => self.cast_touch(5, 1.0, 1),
-            Actions::PreparatoryTouch => self.cast_touch(20, 2.0, 2),
-            Actions::TrainedFinesse => self.cast_touch(0, 1.0, 0),
+            Actions::PrudentTouch => self.cast_touch(durability, quality_e, 1),
+            Actions::PreparatoryTouch => self.cast_touch(durability, quality_e, 2),
+            Actions::TrainedFinesse => self.cast_touch(durability, quality_e, 0),
             Actions::TricksOfTheTrade => {
                 self.craft_points = (self.craft_points + 20).min(self.attributes.craft_points);
                 if !matches!(self.condition, Condition::Good | Condition::Excellent)
@@ -939,11 +1500,11 @@ impl Status {
                 self.buffs.manipulation = self.buffs.manipulation.max(self.new_duration_buff(8));
             }
             Actions::MuscleMemory => {
-                self.cast_synthesis(10, 3.0);
+                self.cast_synthesis(durability, progress_e);
                 self.buffs.muscle_memory = self.new_duration_buff(5);
             }
             Actions::Reflect => {
-                self.cast_touch(10, 3.0, 2);
+                self.cast_touch(durability, quality_e, 2);
             }
             Actions::TrainedEye => {
                 self.quality += self.recipe.quality;
@@ -967,24 +1528,24 @@ impl Status {
                     .final_appraisal
                     .max(self.new_duration_buff(5) - 1);
                 self.combo = None;
-                return;
+                return CastTrace::new(self);
             }
             Actions::CarefulObservation => {
                 self.limits.careful_observation_used += 1;
                 self.combo = None;
-                return;
+                return CastTrace::new(self);
             }
             Actions::HeartAndSoul => {
                 self.buffs.heart_and_soul = 1;
                 self.limits.heart_and_soul_used += 1;
                 self.combo = None;
-                return;
+                return CastTrace::new(self);
             }
             // 7.0
             Actions::RefinedTouch => {
                 self.cast_touch(
-                    10,
-                    1.0,
+                    durability,
+                    quality_e,
                     if let Some(ComboStates::BasicTouched) = self.combo {
                         2
                     } else {
@@ -992,12 +1553,12 @@ impl Status {
                     },
                 );
             }
-            Actions::DaringTouch => self.cast_touch(10, 1.5, 1),
+            Actions::DaringTouch => self.cast_touch(durability, quality_e, 1),
             Actions::QuickInnovation => {
                 self.buffs.innovation = self.buffs.innovation.max(self.new_duration_buff(1) - 1);
                 self.limits.quick_innovation_used += 1;
                 self.combo = None;
-                return;
+                return CastTrace::new(self);
             }
             Actions::ImmaculateMend => {
                 self.durability = self.recipe.durability;
@@ -1009,22 +1570,27 @@ impl Status {
             // 7.4
             Actions::StellarSteadyHand => {
                 self.buffs.stellar_steady_hand = self.new_duration_buff(3);
-                self.limits.stellar_steady_hand_charged -= 1;
+                self.limits.stellar_steady_hand_charged =
+                    self.limits.stellar_steady_hand_charged.saturating_sub(1);
             }
             // fake actions
-            Actions::RapidSynthesisFail => self.consume_durability(10),
-            Actions::HastyTouchFail => self.consume_durability(10),
-            Actions::DaringTouchFail => self.consume_durability(10),
+            Actions::RapidSynthesisFail | Actions::HastyTouchFail | Actions::DaringTouchFail => {
+                self.consume_durability(durability)
+            }
         }
+        let mut trace = CastTrace::new(self);
+        trace.final_appraisal_triggered = final_appraisal > 0 && self.buffs.final_appraisal == 0;
         if self.buffs.manipulation > 0
             && self.durability > 0
             && !matches!(action, Actions::Manipulation)
         {
             self.durability = self.recipe.durability.min(self.durability + 5);
+            trace.manipulation_repaired = self.durability - trace.durability;
         }
         self.buffs.next();
         self.combo = next_combo_state;
         self.step += 1;
+        trace
     }
 
     /// 计算当前状态下某技能的成功概率，返回结果介于[0..=100]之间。
@@ -1051,7 +1617,7 @@ impl Status {
             DurabilityNotEnough, HeartAndSoulUsed, LevelGapMustGreaterThanTen,
             NotAllowedInInnovationBuff, NotAllowedInWastNotBuff, OnlyAllowedInFirstStep,
             PlayerLevelTooLow, QuickInnovationUsed, RequireGoodOrExcellent,
-            RequireHastyTouchSuccessed, RequireInnerQuiet1, RequireInnerQuiet10,
+            RequireHastyTouchSuccessed, RequireInnerQuiet1, RequireInnerQuiet10, RequireSpecialist,
             StellarSteadyHandUsed, TrainedPerfectionUsed,
         };
 
@@ -1082,6 +1648,9 @@ impl Status {
             Actions::ByregotsBlessing if self.buffs.inner_quiet < 1 => Err(RequireInnerQuiet1),
             Actions::TrainedFinesse if self.buffs.inner_quiet != 10 => Err(RequireInnerQuiet10),
 
+            Actions::CarefulObservation | Actions::HeartAndSoul if !self.attributes.specialist => {
+                Err(RequireSpecialist)
+            }
             Actions::CarefulObservation if self.limits.careful_observation_used >= 3 => {
                 Err(CarefulObservationUsed3)
             }
@@ -1101,16 +1670,30 @@ impl Status {
                 Err(StellarSteadyHandUsed)
             }
 
-            _ if self.durability <= 0 => Err(DurabilityNotEnough),
+            _ if self.durability == 0 => Err(DurabilityNotEnough),
             _ if self.craft_point(action) > self.craft_points => Err(CraftPointNotEnough),
             _ if self.progress >= self.recipe.difficulty => Err(CraftingAlreadyFinished),
             _ => Ok(()),
         }
     }
 
+    pub(crate) fn key(&self) -> StateKey {
+        StateKey {
+            buffs: self.buffs,
+            combo: self.combo,
+            limits: self.limits,
+            durability: self.durability,
+            craft_points: self.craft_points,
+            progress: self.progress,
+            quality: self.quality,
+            step: self.step,
+            condition: self.condition,
+        }
+    }
+
     /// 本次制作是否已经结束。
     pub fn is_finished(&self) -> bool {
-        self.progress >= self.recipe.difficulty || self.durability <= 0
+        self.progress >= self.recipe.difficulty || self.durability == 0
     }
 
     /// 计算当前状态的HQ概率。
@@ -1140,7 +1723,6 @@ impl Status {
 ///
 pub struct ConditionIterator {
     flag: i32,
-    rate: f32,
     good_chance: f32,
     step: Option<Condition>,
 }
@@ -1149,7 +1731,6 @@ impl ConditionIterator {
     pub fn new(flag: i32, level: i32) -> Self {
         Self {
             flag,
-            rate: 0.0,
             good_chance: [0.2, 0.25][(level >= 63) as usize],
             step: Some(Condition::Normal),
         }
@@ -1170,28 +1751,10 @@ impl ConditionIterator {
             Condition::Robust => None,
         }
     }
-}
 
-impl Iterator for ConditionIterator {
-    type Item = (Condition, f32);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let cond = match self.step {
-            Some(cond) => {
-                let mut cond = cond;
-                while self.flag & (1 << cond as i32) == 0 {
-                    cond = match Self::next_cond(cond) {
-                        Some(cond) => cond,
-                        None => return None,
-                    };
-                }
-                cond
-            }
-            None => return None,
-        };
-        self.step = Self::next_cond(cond);
+    fn rate(&self, cond: Condition) -> f32 {
         let expert = self.flag & (1 << Condition::Excellent as i32) == 0;
-        let rate = match cond {
+        match cond {
             Condition::Good => [self.good_chance, 0.1][expert as usize],
             Condition::Excellent => [0.04, 0.0][expert as usize],
             Condition::Poor => 0.0,
@@ -1202,10 +1765,257 @@ impl Iterator for ConditionIterator {
             Condition::Primed => 0.1,
             Condition::GoodOmen => 0.1,
             Condition::Robust => 0.1,
-            Condition::Normal => 1.0 - self.rate,
-        };
-        self.rate += rate;
-        Some((cond, rate))
+            Condition::Normal => {
+                // 通常状态的概率为其余所有可能出现的状态的概率之和的补
+                let mut rest = 0.0;
+                let mut cond = Self::next_cond(Condition::Normal);
+                while let Some(c) = cond {
+                    if self.flag & (1 << c as i32) != 0 {
+                        rest += self.rate(c);
+                    }
+                    cond = Self::next_cond(c);
+                }
+                1.0 - rest
+            }
+        }
+    }
+}
+
+impl Iterator for ConditionIterator {
+    type Item = (Condition, f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cond = self.step?;
+        while self.flag & (1 << cond as i32) == 0 {
+            cond = Self::next_cond(cond)?;
+        }
+        self.step = Self::next_cond(cond);
+        Some((cond, self.rate(cond)))
+    }
+}
+
+/// 球色的转移模型。
+///
+/// 在[`ConditionIterator`]给出的各球色出现概率的基础上，考虑了游戏中的强制转移规则：
+/// 最高品质之后必定是低品质，好兆头之后必定是高品质，强韧之后必定是结实。
+///
+/// Examples:
+///
+/// ```rust
+/// use ffxiv_crafting::{Condition, ConditionModel};
+///
+/// let model = ConditionModel::new(15, 90);
+/// assert_eq!(model.next(Condition::Excellent), [(Condition::Poor, 1.0)]);
+/// let p: f32 = model.next(Condition::Normal).iter().map(|(_, p)| p).sum();
+/// assert!((p - 1.0).abs() < 1e-6);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConditionModel {
+    distribution: Vec<(Condition, f32)>,
+}
+
+impl ConditionModel {
+    pub fn new(flag: i32, level: i32) -> Self {
+        Self {
+            distribution: ConditionIterator::new(flag, level)
+                .filter(|&(_, p)| p > 0.0)
+                .collect(),
+        }
+    }
+
+    /// 使用当前制作的配方与玩家等级构造转移模型
+    pub fn from_status(s: &Status) -> Self {
+        Self::new(s.recipe.conditions_flag as i32, s.attributes.level as i32)
+    }
+
+    /// 给定当前球色，返回下一工次各球色出现的概率
+    pub fn next(&self, current: Condition) -> &[(Condition, f32)] {
+        match current {
+            Condition::Excellent => &[(Condition::Poor, 1.0)],
+            Condition::GoodOmen => &[(Condition::Good, 1.0)],
+            Condition::Robust => &[(Condition::Sturdy, 1.0)],
+            _ => &self.distribution,
+        }
+    }
+
+    /// 给定当前球色，随机抽取下一工次的球色
+    pub fn sample<R: random::CraftRng + ?Sized>(
+        &self,
+        current: Condition,
+        rng: &mut R,
+    ) -> Condition {
+        let next = self.next(current);
+        let mut x = rng.next_f64() as f32;
+        for &(c, p) in next {
+            if x < p {
+                return c;
+            }
+            x -= p;
+        }
+        // 浮点误差导致未能选中时，取最后一项
+        next.last().map_or(Condition::Normal, |&(c, _)| c)
+    }
+}
+
+/// 设置下一工次球色时违反的规则
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionError {
+    /// 该球色不会在本配方中出现
+    NotAllowedByRecipe(Condition),
+    /// 当前球色决定了下一工次必定为某种球色
+    Forced {
+        expected: Condition,
+        found: Condition,
+    },
+}
+
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConditionError::NotAllowedByRecipe(c) => {
+                write!(f, "condition {:?} is not allowed by recipe", c)
+            }
+            ConditionError::Forced { expected, found } => {
+                write!(
+                    f,
+                    "next condition must be {:?}, found {:?}",
+                    expected, found
+                )
+            }
+        }
+    }
+}
+
+impl Error for ConditionError {}
+
+/// 推进一个工次时发生的错误
+#[derive(Debug)]
+pub enum StepError {
+    /// 技能无法发动
+    Cast(CastActionError),
+    /// 给定的下一工次球色不合法
+    Condition(ConditionError),
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::Cast(e) => Display::fmt(e, f),
+            StepError::Condition(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl Error for StepError {}
+
+impl From<CastActionError> for StepError {
+    fn from(e: CastActionError) -> Self {
+        StepError::Cast(e)
+    }
+}
+
+impl From<ConditionError> for StepError {
+    fn from(e: ConditionError) -> Self {
+        StepError::Condition(e)
+    }
+}
+
+impl Status {
+    /// 由当前球色强制决定的下一工次球色，没有强制规则时返回None
+    pub fn forced_next_condition(&self) -> Option<Condition> {
+        match self.condition {
+            Condition::Excellent => Some(Condition::Poor),
+            Condition::GoodOmen => Some(Condition::Good),
+            Condition::Robust => Some(Condition::Sturdy),
+            _ => None,
+        }
+    }
+
+    /// 检查某球色能否作为当前状态的下一工次球色
+    pub fn check_next_condition(&self, next: Condition) -> Result<(), ConditionError> {
+        if let Some(expected) = self.forced_next_condition() {
+            if expected != next {
+                return Err(ConditionError::Forced {
+                    expected,
+                    found: next,
+                });
+            }
+        } else if self.recipe.conditions_flag & (1 << next as u16) == 0 {
+            return Err(ConditionError::NotAllowedByRecipe(next));
+        }
+        Ok(())
+    }
+
+    /// 发动一次技能并将球色推进到下一工次。
+    ///
+    /// 当前球色有强制转移规则时，`next`必须与规则给出的球色一致；
+    /// 否则`next`必须是本配方可能出现的球色。
+    /// 不消耗工次的技能（如最终确认）不会改变球色，此时`next`被忽略；
+    /// 设计变动虽然不消耗工次，但会改变球色。
+    /// 出错时不改变当前状态。
+    pub fn try_step(&mut self, action: Actions, next: Condition) -> Result<CastOutcome, StepError> {
+        self.is_action_allowed(action)?;
+        let mut s = self.clone();
+        let outcome = s.cast_action_with_outcome(action);
+        if s.condition_advanced(self, action) {
+            self.check_next_condition(next)?;
+            s.condition = next;
+        }
+        *self = s;
+        Ok(outcome)
+    }
+
+    /// 根据成功率随机发动一次技能，并按照转移模型随机推进到下一工次的球色。
+    /// 返回实际发动的技能（或其失败时对应的假技能）以及技能造成的变化。
+    pub fn try_step_random<R: random::CraftRng + ?Sized>(
+        &mut self,
+        action: Actions,
+        model: &ConditionModel,
+        rng: &mut R,
+    ) -> Result<(Actions, CastOutcome), CastActionError> {
+        let before = self.clone();
+        let result = self.try_cast_action_random(action, rng)?;
+        if self.condition_advanced(&before, action) {
+            self.condition = model.sample(before.condition, rng);
+        }
+        Ok(result)
+    }
+
+    /// 枚举发动一次技能后所有可能的结果及其概率，
+    /// 包括有成功率的技能是否成功，以及按照转移模型推进的下一工次球色。
+    /// 若当前状态不允许发动该技能，则返回错误。
+    pub fn transitions(
+        &self,
+        action: Actions,
+        model: &ConditionModel,
+    ) -> Result<Vec<(f64, Status)>, CastActionError> {
+        self.is_action_allowed(action)?;
+        let rate = self.success_rate(action) as f64 / 100.0;
+        let mut casts = vec![(rate, action)];
+        if let Some(fail) = action.fail_action().filter(|_| rate < 1.0) {
+            casts.push((1.0 - rate, fail));
+        }
+        let mut result = Vec::new();
+        for (p, action) in casts {
+            let mut s = self.clone();
+            s.cast_action(action);
+            if s.condition_advanced(self, action) {
+                for &(c, pc) in model.next(self.condition) {
+                    let mut s = s.clone();
+                    s.condition = c;
+                    result.push((p * pc as f64, s));
+                }
+            } else {
+                result.push((p, s));
+            }
+        }
+        Ok(result)
+    }
+
+    /// 从`before`发动`action`得到当前状态后，是否需要推进到下一工次的球色
+    fn condition_advanced(&self, before: &Status, action: Actions) -> bool {
+        !self.is_finished() && (self.step != before.step || action == Actions::CarefulObservation)
     }
 }
 
@@ -1213,7 +2023,10 @@ impl Iterator for ConditionIterator {
 mod tests {
     use test::Bencher;
 
-    use crate::{data, Actions, Attributes, Condition, Recipe, Status};
+    use crate::{
+        data, locale::Locale, ActionCategory, Actions, Attributes, Buff, CastActionError,
+        Condition, Recipe, Status,
+    };
 
     #[test]
     fn option_actions() {
@@ -1226,6 +2039,325 @@ mod tests {
         }
     }
 
+    #[test]
+    fn simulate() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: false,
+        };
+        let s = Status::new(
+            attr,
+            Recipe::new(data::recipe_level_table(560), 100, 100, 100),
+        );
+        let actions = [
+            Actions::MuscleMemory,
+            Actions::Veneration,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::BasicTouch,
+        ];
+        let report = s.simulate(&actions);
+        assert!(report.illegal.is_none());
+        assert!(report.completed);
+        assert!(report.finished_early());
+        assert_eq!(report.steps.len() + report.unused, actions.len());
+        let mut replay = s.clone();
+        for (step, &action) in report.steps.iter().zip(&actions) {
+            assert_eq!(step.action, action);
+            replay.cast_action(action);
+            assert_eq!(step.status.key(), replay.key());
+        }
+        assert_eq!(report.progress, replay.progress);
+        assert_eq!(
+            report.high_quality,
+            replay.high_quality_probability().unwrap()
+        );
+
+        let report = s.simulate(&[Actions::BasicSynthesis, Actions::ByregotsBlessing]);
+        assert!(!report.completed);
+        assert!(!report.finished_early());
+        assert_eq!(report.high_quality, 0);
+        assert_eq!(
+            report.illegal.map(|x| (x.index, x.error)),
+            Some((1, CastActionError::RequireInnerQuiet1))
+        );
+    }
+
+    #[test]
+    fn specialist() {
+        let attr = Attributes::new(90, 4048, 4005, 594);
+        assert!(!attr.specialist);
+        assert!(!Attributes::default().specialist);
+        let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        s.condition = Condition::Good;
+        for action in [Actions::CarefulObservation, Actions::HeartAndSoul] {
+            assert_eq!(
+                s.is_action_allowed(action),
+                Err(CastActionError::RequireSpecialist)
+            );
+        }
+        // 非专家的求解结果中不会出现专家技能
+        let solution = crate::solver::quality::solve(&s).unwrap();
+        assert!(!solution.actions.contains(&Actions::HeartAndSoul));
+
+        let specialist = attr.with_specialist();
+        assert!(specialist.specialist);
+        assert_eq!(specialist.craftsmanship, 4048 + 20);
+        assert_eq!(specialist.control, 4005 + 20);
+        assert_eq!(specialist.craft_points, 594 + 15);
+        assert_eq!(specialist.with_specialist(), specialist);
+        assert_eq!(specialist.without_specialist(), attr);
+
+        let s = Status::new(specialist, recipe);
+        assert!(s.is_action_allowed(Actions::CarefulObservation).is_ok());
+        assert!(s.is_action_allowed(Actions::HeartAndSoul).is_ok());
+    }
+
+    #[test]
+    fn action_info() {
+        let attr = Attributes {
+            level: 100,
+            craftsmanship: 5000,
+            control: 5000,
+            craft_points: 600,
+            specialist: false,
+        };
+        let s = Status::new(
+            attr,
+            Recipe::new(data::recipe_level_table(770), 100, 100, 100),
+        );
+        for &action in Actions::all() {
+            let info = action.info(attr.level);
+            assert_eq!(info.craft_points, s.craft_point(action));
+            assert_eq!(info.fake, action.name(Locale::En).is_none());
+        }
+        let info = Actions::DelicateSynthesis.info(90);
+        assert_eq!(info.category, ActionCategory::Synthesis);
+        assert_eq!(
+            (info.progress_efficiency, info.quality_efficiency),
+            (100, 100)
+        );
+        assert_eq!(Actions::DelicateSynthesis.info(94).progress_efficiency, 150);
+        assert_eq!(Actions::Manipulation.info(90).unlock_level, 65);
+    }
+
+    #[test]
+    fn cast_outcome() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        s.cast_action(Actions::Manipulation);
+        let outcome = s.cast_action_with_outcome(Actions::BasicTouch);
+        assert_eq!(outcome.durability_lost, 10);
+        assert_eq!(outcome.manipulation_repaired, 5);
+        assert_eq!(outcome.inner_quiet, 1);
+        assert_eq!(outcome.craft_points_spent, 18);
+
+        s.cast_action(Actions::Veneration);
+        s.cast_action(Actions::FinalAppraisal);
+        let outcome = s.cast_action_with_outcome(Actions::Veneration);
+        assert_eq!(outcome.buffs_refreshed, [Buff::Veneration]);
+        for _ in 0..3 {
+            s.cast_action(Actions::Groundwork);
+        }
+        let outcome = s.cast_action_with_outcome(Actions::Groundwork);
+        assert!(outcome.final_appraisal_triggered);
+        assert_eq!(
+            outcome.buffs_expired,
+            [Buff::Veneration, Buff::FinalAppraisal]
+        );
+        assert_eq!(s.progress, s.recipe.difficulty - 1);
+
+        s.cast_action(Actions::TricksOfTheTrade);
+        s.condition = Condition::Good;
+        let outcome = s.cast_action_with_outcome(Actions::TricksOfTheTrade);
+        assert_eq!(outcome.craft_points_restored, 20);
+    }
+
+    #[test]
+    fn cast_outcome_without_step() {
+        let attr = Attributes {
+            level: 100,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: true,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        s.cast_action(Actions::Veneration);
+        s.cast_action(Actions::GreatStrides);
+        // 不消耗工次的技能不会刷新其他buff
+        for (action, applied) in [
+            (Actions::FinalAppraisal, vec![Buff::FinalAppraisal]),
+            (Actions::CarefulObservation, vec![]),
+            (Actions::HeartAndSoul, vec![Buff::HeartAndSoul]),
+            (Actions::QuickInnovation, vec![Buff::Innovation]),
+        ] {
+            let outcome = s.clone().cast_action_with_outcome(action);
+            assert_eq!(outcome.buffs_applied, applied, "{:?}", action);
+            assert!(outcome.buffs_refreshed.is_empty(), "{:?}", action);
+            assert!(outcome.buffs_expired.is_empty(), "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn try_cast_action() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 20,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        assert!(matches!(
+            s.try_cast_action(Actions::StellarSteadyHand),
+            Err(CastActionError::StellarSteadyHandUsed)
+        ));
+        assert!(matches!(
+            s.after(Actions::Veneration),
+            Ok(Status { step: 1, .. })
+        ));
+        assert_eq!(s.step, 0);
+
+        let outcome = s.try_cast_action(Actions::BasicTouch).unwrap();
+        assert_eq!(outcome.craft_points_spent, 18);
+        assert!(matches!(
+            s.try_cast_action(Actions::BasicTouch),
+            Err(CastActionError::CraftPointNotEnough)
+        ));
+        assert_eq!((s.craft_points, s.step), (2, 1));
+    }
+
+    #[test]
+    fn condition_model() {
+        use crate::{random::SplitMix64, ConditionModel};
+
+        let model = ConditionModel::new(15, 90);
+        let expected = [
+            (Condition::Normal, 0.71),
+            (Condition::Good, 0.25),
+            (Condition::Excellent, 0.04),
+        ];
+        let next = model.next(Condition::Normal);
+        assert_eq!(next.len(), expected.len());
+        for (&(c, p), (ec, ep)) in next.iter().zip(expected) {
+            assert_eq!(c, ec);
+            assert!((p - ep).abs() < 1e-6);
+        }
+        let expert = ConditionModel::new(0b111_1111_0011, 100);
+        assert_eq!(expert.next(Condition::GoodOmen), [(Condition::Good, 1.0)]);
+        assert_eq!(expert.next(Condition::Robust), [(Condition::Sturdy, 1.0)]);
+        assert!(expert
+            .next(Condition::Good)
+            .iter()
+            .all(|&(c, _)| c != Condition::Excellent && c != Condition::Poor));
+
+        let mut rng = SplitMix64::new(0);
+        let n = 10000;
+        let good = (0..n)
+            .filter(|_| model.sample(Condition::Normal, &mut rng) == Condition::Good)
+            .count();
+        assert!((2200..2800).contains(&good), "{}", good);
+        for _ in 0..100 {
+            assert_eq!(
+                model.sample(Condition::Excellent, &mut rng),
+                Condition::Poor
+            );
+        }
+    }
+
+    #[test]
+    fn step_conditions() {
+        use crate::{random::SplitMix64, ConditionError, ConditionModel, StepError};
+
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        assert!(matches!(
+            s.try_step(Actions::Observe, Condition::Pliant),
+            Err(StepError::Condition(ConditionError::NotAllowedByRecipe(
+                Condition::Pliant
+            )))
+        ));
+        assert_eq!(s.step, 0);
+        s.try_step(Actions::Observe, Condition::Excellent).unwrap();
+        assert!(matches!(
+            s.try_step(Actions::BasicTouch, Condition::Normal),
+            Err(StepError::Condition(ConditionError::Forced {
+                expected: Condition::Poor,
+                found: Condition::Normal
+            }))
+        ));
+        s.try_step(Actions::FinalAppraisal, Condition::Good)
+            .unwrap();
+        assert_eq!(s.condition, Condition::Excellent);
+        s.try_step(Actions::BasicTouch, Condition::Poor).unwrap();
+        assert_eq!(s.condition, Condition::Poor);
+
+        let model = ConditionModel::from_status(&s);
+        let mut rng = SplitMix64::new(5);
+        s.condition = Condition::Excellent;
+        s.try_step_random(Actions::Observe, &model, &mut rng)
+            .unwrap();
+        assert_eq!(s.condition, Condition::Poor);
+    }
+
+    #[test]
+    fn careful_observation_conditions() {
+        use crate::{random::SplitMix64, ConditionModel};
+
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 594,
+            specialist: true,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(640), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        s.try_step(Actions::CarefulObservation, Condition::Good)
+            .unwrap();
+        assert_eq!(s.condition, Condition::Good);
+        assert_eq!(s.step, 0);
+
+        let model = ConditionModel::from_status(&s);
+        let mut rng = SplitMix64::new(5);
+        s.condition = Condition::Excellent;
+        s.try_step_random(Actions::CarefulObservation, &model, &mut rng)
+            .unwrap();
+        assert_eq!(s.condition, Condition::Poor);
+
+        s.condition = Condition::Normal;
+        let branches = s.transitions(Actions::CarefulObservation, &model).unwrap();
+        assert_eq!(branches.len(), model.next(Condition::Normal).len());
+        assert!(branches
+            .iter()
+            .any(|(_, next)| next.condition == Condition::Good));
+        let total: f64 = branches.iter().map(|(p, _)| p).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn basic_synth() {
         let attr = Attributes {
@@ -1233,6 +2365,7 @@ mod tests {
             craftsmanship: 2806,
             control: 2784,
             craft_points: 548,
+            specialist: false,
         };
         let rlv = data::recipe_level_table(517);
         let recipe = Recipe::new(rlv, 50, 100, 50);
@@ -1252,6 +2385,7 @@ mod tests {
             craftsmanship: 2762,
             control: 2794,
             craft_points: 539,
+            specialist: false,
         };
         let rlv = data::recipe_level_table(517);
         let recipe = Recipe::new(rlv, 100, 100, 100);
@@ -1370,6 +2504,7 @@ mod tests {
             craftsmanship: 3293,
             control: 3524,
             craft_points: 626,
+            specialist: false,
         };
         let s = Status::new(player, recipe);
         let actions = [
@@ -1420,6 +2555,7 @@ mod tests {
             craftsmanship: 3258,
             control: 3340,
             craft_points: 654,
+            specialist: false,
         };
         let mut s = Status::new(player, recipe);
         let actions = vec![
@@ -1458,6 +2594,7 @@ mod tests {
             craftsmanship: 4048,
             control: 4005,
             craft_points: 594,
+            specialist: false,
         };
         let mut s = Status::new(player, recipe);
         s.cast_action(Actions::Veneration);
@@ -1480,6 +2617,7 @@ mod tests {
             craftsmanship: 5668,
             control: 5290,
             craft_points: 615,
+            specialist: false,
         };
         let mut s = Status::new(player, recipe);
         s.cast_action(Actions::WasteNotII);
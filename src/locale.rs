@@ -0,0 +1,382 @@
+//! 多语言支持。
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use crate::{data, Actions, Buff, CastActionError, Condition};
+
+/// 游戏客户端的语言
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub enum Locale {
+    /// 英语
+    #[default]
+    En,
+    /// 日语
+    Ja,
+    /// 德语
+    De,
+    /// 法语
+    Fr,
+    /// 中文
+    Zh,
+    /// 韩语
+    Ko,
+}
+
+impl Actions {
+    /// 技能在指定语言客户端中的名称，用于模拟失败结果的假技能没有对应的名称。
+    ///
+    /// Example:
+    /// ```rust
+    /// use ffxiv_crafting::{locale::Locale, Actions};
+    ///
+    /// assert_eq!(Actions::ByregotsBlessing.name(Locale::En), Some("Byregot's Blessing"));
+    /// assert_eq!(Actions::ByregotsBlessing.name(Locale::Zh), Some("比尔格的祝福"));
+    /// assert_eq!(Actions::HastyTouchFail.name(Locale::En), None);
+    /// ```
+    pub fn name(&self, locale: Locale) -> Option<&'static str> {
+        data::ACTION_NAMES
+            .iter()
+            .find(|(a, _)| a == self)
+            .map(|(_, names)| names[locale as usize])
+    }
+}
+
+/// 根据任意语言客户端中的技能名称查找技能，不区分英文字母的大小写。
+pub(crate) fn find_action(name: &str) -> Option<Actions> {
+    data::ACTION_NAMES
+        .iter()
+        .find(|(_, names)| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+        .map(|&(action, _)| action)
+}
+
+impl Condition {
+    /// 制作状态在指定语言客户端中的名称
+    pub fn name(&self, locale: Locale) -> &'static str {
+        Self::names(self)[locale as usize]
+    }
+
+    /// 玩家间常用的球色叫法，例如白球、红球、彩球、黑球。
+    /// 强韧状态没有通用的球色叫法。
+    pub fn ball_name(&self, locale: Locale) -> Option<&'static str> {
+        Self::ball_names(self).map(|names| names[locale as usize])
+    }
+
+    #[rustfmt::skip]
+    fn names(&self) -> [&'static str; 6] {
+        match self {
+            Condition::Normal => ["Normal", "通常", "Normal", "Normal", "通常", "보통"],
+            Condition::Good => ["Good", "高品質", "Gut", "Bonne", "高品质", "고품질"],
+            Condition::Excellent => ["Excellent", "最高品質", "Exzellent", "Excellente", "最高品质", "최고품질"],
+            Condition::Poor => ["Poor", "低品質", "Schlecht", "Médiocre", "低品质", "저품질"],
+            Condition::Centered => ["Centered", "安定", "Zentriert", "Centrée", "安定", "안정"],
+            Condition::Sturdy => ["Sturdy", "頑丈", "Stabil", "Solide", "结实", "견고"],
+            Condition::Pliant => ["Pliant", "高能率", "Ergiebig", "Souple", "高效", "고효율"],
+            Condition::Malleable => ["Malleable", "高進捗", "Formbar", "Malléable", "高进展", "고진척"],
+            Condition::Primed => ["Primed", "長持続", "Vorbereitet", "Préparée", "长持续", "장기지속"],
+            Condition::GoodOmen => ["Good Omen", "良兆候", "Gutes Omen", "Bon présage", "好兆头", "좋은 징조"],
+            Condition::Robust => ["Robust", "強靭", "Robust", "Robuste", "强韧", "강인"],
+        }
+    }
+
+    #[rustfmt::skip]
+    fn ball_names(&self) -> Option<[&'static str; 6]> {
+        Some(match self {
+            Condition::Normal => ["White", "白", "Weiß", "Blanc", "白球", "흰색"],
+            Condition::Good => ["Red", "赤", "Rot", "Rouge", "红球", "빨간색"],
+            Condition::Excellent => ["Rainbow", "虹", "Regenbogen", "Arc-en-ciel", "彩球", "무지개색"],
+            Condition::Poor => ["Black", "黒", "Schwarz", "Noir", "黑球", "검은색"],
+            Condition::Centered => ["Yellow", "黄", "Gelb", "Jaune", "黄球", "노란색"],
+            Condition::Sturdy => ["Blue", "青", "Blau", "Bleu", "蓝球", "파란색"],
+            Condition::Pliant => ["Green", "緑", "Grün", "Vert", "绿球", "초록색"],
+            Condition::Malleable => ["Dark Blue", "紺", "Dunkelblau", "Bleu foncé", "深蓝球", "남색"],
+            Condition::Primed => ["Purple", "紫", "Lila", "Violet", "紫球", "보라색"],
+            Condition::GoodOmen => ["Pink", "ピンク", "Rosa", "Rose", "粉球", "분홍색"],
+            Condition::Robust => return None,
+        })
+    }
+}
+
+/// 根据任意语言客户端中的制作状态名称或球色叫法查找制作状态，不区分英文字母的大小写。
+pub(crate) fn find_condition(name: &str) -> Option<Condition> {
+    let matches = |names: [&str; 6]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+    [
+        Condition::Normal,
+        Condition::Good,
+        Condition::Excellent,
+        Condition::Poor,
+        Condition::Centered,
+        Condition::Sturdy,
+        Condition::Pliant,
+        Condition::Malleable,
+        Condition::Primed,
+        Condition::GoodOmen,
+        Condition::Robust,
+    ]
+    .into_iter()
+    .find(|c| matches(c.names()) || c.ball_names().is_some_and(matches))
+}
+
+impl Buff {
+    /// buff在指定语言客户端中的名称
+    pub fn name(&self, locale: Locale) -> &'static str {
+        let action = match self {
+            Buff::InnerQuiet => {
+                return [
+                    "Inner Quiet",
+                    "インナークワイエット",
+                    "Innere Ruhe",
+                    "Calme intérieur",
+                    "内静",
+                    "내면의 고요",
+                ][locale as usize]
+            }
+            Buff::Expedience => {
+                return [
+                    "Expedience",
+                    "匠の好機",
+                    "Zügigkeit",
+                    "Opportunité",
+                    "工匠的好机",
+                    "장인의 호기",
+                ][locale as usize]
+            }
+            Buff::MuscleMemory => Actions::MuscleMemory,
+            Buff::GreatStrides => Actions::GreatStrides,
+            Buff::Veneration => Actions::Veneration,
+            Buff::Innovation => Actions::Innovation,
+            Buff::FinalAppraisal => Actions::FinalAppraisal,
+            Buff::Manipulation => Actions::Manipulation,
+            Buff::WasteNot => Actions::WasteNot,
+            Buff::WasteNotII => Actions::WasteNotII,
+            Buff::HeartAndSoul => Actions::HeartAndSoul,
+            Buff::TrainedPerfection => Actions::TrainedPerfection,
+            Buff::StellarSteadyHand => Actions::StellarSteadyHand,
+        };
+        // 其余buff与施加它的技能同名
+        action.name(locale).unwrap()
+    }
+}
+
+impl CastActionError {
+    /// 错误信息在指定语言下的描述
+    pub fn message(&self, locale: Locale) -> &'static str {
+        let messages = match self {
+            CastActionError::DurabilityNotEnough => [
+                "durability not enough",
+                "耐久が足りません",
+                "Nicht genug Haltbarkeit",
+                "Solidité insuffisante",
+                "耐久不足",
+                "내구도가 부족합니다",
+            ],
+            CastActionError::CraftPointNotEnough => [
+                "craft point not enough",
+                "CPが足りません",
+                "Nicht genug CP",
+                "PS insuffisants",
+                "制作力不足",
+                "CP가 부족합니다",
+            ],
+            CastActionError::CraftingAlreadyFinished => [
+                "crafting already finished",
+                "製作は既に完了しています",
+                "Die Herstellung ist bereits abgeschlossen",
+                "La fabrication est déjà terminée",
+                "制作已完成，无法发动技能",
+                "제작이 이미 완료되었습니다",
+            ],
+            CastActionError::PlayerLevelTooLow => [
+                "player level too low",
+                "レベルが足りません",
+                "Stufe zu niedrig",
+                "Niveau trop bas",
+                "该技能尚未学会，玩家等级不足",
+                "레벨이 부족합니다",
+            ],
+            CastActionError::RequireGoodOrExcellent => [
+                "require good or excellent",
+                "状態が「高品質」以上の時のみ使用できます",
+                "Nur bei „Gut“ oder „Exzellent“ einsetzbar",
+                "Utilisable uniquement en qualité bonne ou excellente",
+                "该技能只有在“高品质”及以上的状态下才能使用",
+                "상태가 고품질 이상일 때만 사용할 수 있습니다",
+            ],
+            CastActionError::NotAllowedInWastNotBuff => [
+                "not allowed in wast not buff",
+                "倹約中は使用できません",
+                "Während Nachhaltigkeit nicht einsetzbar",
+                "Inutilisable sous Parcimonie",
+                "该技能在俭约及长期俭约状态下无法使用",
+                "근검절약 중에는 사용할 수 없습니다",
+            ],
+            CastActionError::OnlyAllowedInFirstStep => [
+                "only allowed in first step",
+                "最初の工程でのみ使用できます",
+                "Nur im ersten Arbeitsschritt einsetzbar",
+                "Utilisable uniquement à la première étape",
+                "该技能仅可在首次作业时发动",
+                "첫 번째 공정에서만 사용할 수 있습니다",
+            ],
+            CastActionError::LevelGapMustGreaterThanTen => [
+                "level gap must greater than 10",
+                "自分より10レベル以上低いレシピでのみ使用できます",
+                "Nur bei Rezepten, die mindestens 10 Stufen niedriger sind",
+                "Utilisable uniquement sur les recettes d'au moins 10 niveaux inférieurs",
+                "该技能仅可用于等级低了10级及以上的配方",
+                "자신보다 10레벨 이상 낮은 레시피에서만 사용할 수 있습니다",
+            ],
+            CastActionError::RequireInnerQuiet1 => [
+                "require at least 1 stack of inner quiet",
+                "インナークワイエットが1以上の時のみ使用できます",
+                "Erfordert mindestens 1 Stufe Innere Ruhe",
+                "Nécessite au moins 1 charge de Calme intérieur",
+                "该技能只有在内静至少为1层时才可以使用",
+                "내면의 고요가 1 이상일 때만 사용할 수 있습니다",
+            ],
+            CastActionError::RequireInnerQuiet10 => [
+                "require 10 stack of inner quiet",
+                "インナークワイエットが10の時のみ使用できます",
+                "Erfordert 10 Stufen Innere Ruhe",
+                "Nécessite 10 charges de Calme intérieur",
+                "该技能只有在内静为10层时才可以使用",
+                "내면의 고요가 10일 때만 사용할 수 있습니다",
+            ],
+            CastActionError::RequireSpecialist => [
+                "require a specialist",
+                "スペシャリストのみ使用できます",
+                "Nur für Spezialisten",
+                "Réservé aux spécialistes",
+                "该技能只有专家才能使用",
+                "전문가만 사용할 수 있습니다",
+            ],
+            CastActionError::CarefulObservationUsed3 => [
+                "careful observation can only use 3 times",
+                "設計変更は3回までしか使用できません",
+                "Sorgfältige Beobachtung kann nur dreimal eingesetzt werden",
+                "Observation méticuleuse ne peut être utilisée que 3 fois",
+                "设计变动最多使用三次",
+                "설계 변경은 3번까지만 사용할 수 있습니다",
+            ],
+            CastActionError::HeartAndSoulUsed => [
+                "heart and soul can be only used once",
+                "一心不乱は1回しか使用できません",
+                "Mit Leib und Seele kann nur einmal eingesetzt werden",
+                "Attention totale ne peut être utilisée qu'une fois",
+                "专心致志一次制作只能使用一次",
+                "일심불란은 1번만 사용할 수 있습니다",
+            ],
+            CastActionError::RequireHastyTouchSuccessed => [
+                "require hasty touch successed first",
+                "ヘイスティタッチの成功後のみ使用できます",
+                "Nur nach erfolgreicher Hastiger Veredelung einsetzbar",
+                "Utilisable uniquement après un Ouvrage hâtif réussi",
+                "必须在仓促成功后使用",
+                "성급한 손길 성공 후에만 사용할 수 있습니다",
+            ],
+            CastActionError::QuickInnovationUsed => [
+                "quick innovation can be only used once",
+                "クイックイノベーションは1回しか使用できません",
+                "Schnelle Innovation kann nur einmal eingesetzt werden",
+                "Innovation rapide ne peut être utilisée qu'une fois",
+                "快速改革一次制作只能使用一次",
+                "신속한 혁신은 1번만 사용할 수 있습니다",
+            ],
+            CastActionError::NotAllowedInInnovationBuff => [
+                "not allowed in innovation buff",
+                "イノベーション中は使用できません",
+                "Während Innovation nicht einsetzbar",
+                "Inutilisable sous Innovation",
+                "改革状态下无法发动快速改革",
+                "혁신 중에는 사용할 수 없습니다",
+            ],
+            CastActionError::TrainedPerfectionUsed => [
+                "trained perfection can be only used once",
+                "匠の絶技は1回しか使用できません",
+                "Meisterliche Perfektion kann nur einmal eingesetzt werden",
+                "Perfection experte ne peut être utilisée qu'une fois",
+                "工匠的绝技一次制作只能使用一次",
+                "장인의 묘기는 1번만 사용할 수 있습니다",
+            ],
+            CastActionError::StellarSteadyHandUsed => [
+                "stellar steady hand can be only used once",
+                "ステラー・ステディハンドは1回しか使用できません",
+                "Stellare ruhige Hand kann nur einmal eingesetzt werden",
+                "Main stable stellaire ne peut être utilisée qu'une fois",
+                "宇宙稳手一次制作只能使用一次",
+                "별빛 안정된 손길은 1번만 사용할 수 있습니다",
+            ],
+        };
+        messages[locale as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locale;
+    use crate::{Actions, Buff, CastActionError, Condition};
+
+    #[test]
+    fn localized_names_round_trip() {
+        let locales = [
+            Locale::En,
+            Locale::Ja,
+            Locale::De,
+            Locale::Fr,
+            Locale::Zh,
+            Locale::Ko,
+        ];
+        for &action in &[
+            Actions::BasicSynthesis,
+            Actions::TricksOfTheTrade,
+            Actions::WasteNotII,
+            Actions::HeartAndSoul,
+            Actions::StellarSteadyHand,
+        ] {
+            for locale in locales {
+                let name = action.name(locale).unwrap();
+                assert_eq!(Actions::try_from(name).unwrap(), action, "{}", name);
+            }
+            let snake_case: &str = (&action).into();
+            assert_eq!(Actions::try_from(snake_case).unwrap(), action);
+        }
+        assert!(Actions::try_from("tricks of the trade").is_ok());
+        assert!(Actions::try_from("tricks of trade").is_err());
+    }
+
+    #[test]
+    fn localized_conditions() {
+        assert_eq!(Condition::Good.name(Locale::Ja), "高品質");
+        assert_eq!(Condition::Excellent.ball_name(Locale::Zh), Some("彩球"));
+        assert_eq!(Condition::Robust.ball_name(Locale::En), None);
+        assert!(matches!(
+            Condition::try_from("good_omen"),
+            Ok(Condition::GoodOmen)
+        ));
+        assert!(matches!(Condition::try_from("红球"), Ok(Condition::Good)));
+        assert!(matches!(
+            Condition::try_from("Malléable"),
+            Ok(Condition::Malleable)
+        ));
+        assert!(Condition::try_from("blurple").is_err());
+    }
+
+    #[test]
+    fn localized_buffs_and_errors() {
+        assert_eq!(Buff::InnerQuiet.name(Locale::Zh), "内静");
+        assert_eq!(Buff::WasteNotII.name(Locale::En), "Waste Not II");
+        assert_eq!(
+            CastActionError::NotAllowedInWastNotBuff.to_string(),
+            "not allowed in wast not buff"
+        );
+        // 英文的错误信息与之前的Display输出保持一致
+        assert_eq!(
+            CastActionError::RequireInnerQuiet10.to_string(),
+            "require 10 stack of inner quiet"
+        );
+        assert_eq!(
+            CastActionError::DurabilityNotEnough.message(Locale::Zh),
+            "耐久不足"
+        );
+    }
+}
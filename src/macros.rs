@@ -0,0 +1,376 @@
+//! 游戏内制作宏的解析与导出。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{macros, Actions};
+//!
+//! let text = r#"
+//! /mlock
+//! /ac "Muscle Memory" <wait.3>
+//! /ac 制作 <wait.3>
+//! /ac 倹約加工 <wait.3>
+//! /echo 完成 <se.1>
+//! "#;
+//! let actions = macros::parse(text).unwrap();
+//! assert_eq!(
+//!     actions,
+//!     [Actions::MuscleMemory, Actions::BasicSynthesis, Actions::PrudentTouch]
+//! );
+//! ```
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::locale::{self, Locale};
+use crate::Actions;
+
+/// 宏解析错误，`line`为出错的行号（从1开始计数）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMacroError {
+    /// 出错的行号
+    pub line: usize,
+    /// 错误类型
+    pub kind: ParseMacroErrorKind,
+}
+
+/// 宏解析错误的类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMacroErrorKind {
+    /// 无法识别的文本指令
+    UnknownCommand(String),
+    /// 无法识别的技能名称
+    UnknownAction(String),
+    /// 技能指令后缺少技能名称
+    MissingAction,
+}
+
+impl Display for ParseMacroError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseMacroErrorKind::UnknownCommand(cmd) => write!(f, "unknown command {:?}", cmd),
+            ParseMacroErrorKind::UnknownAction(name) => write!(f, "unknown action {:?}", name),
+            ParseMacroErrorKind::MissingAction => f.write_str("missing action name"),
+        }
+    }
+}
+
+impl Error for ParseMacroError {}
+
+/// 将多行的游戏内宏文本解析为技能序列。
+///
+/// 支持英语、日语、德语、法语、中文和韩语客户端的技能名称，技能名可以带引号也可以不带。
+/// 空行以及`/echo`、`/mlock`、`/wait`等不影响制作的指令会被忽略。
+pub fn parse(text: &str) -> Result<Vec<Actions>, ParseMacroError> {
+    let mut actions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let err = |kind| ParseMacroError { line: i + 1, kind };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match command {
+            "/ac" | "/action" => {}
+            "/echo" | "/e" | "/mlock" | "/macrolock" | "/wait" => continue,
+            _ => {
+                return Err(err(ParseMacroErrorKind::UnknownCommand(
+                    command.to_string(),
+                )))
+            }
+        }
+        // 去掉<wait.3>、<se.1>等占位符及技能名两侧的引号
+        let name = args.split('<').next().unwrap_or_default().trim();
+        let name = name.trim_matches('"').trim();
+        if name.is_empty() {
+            return Err(err(ParseMacroErrorKind::MissingAction));
+        }
+        match locale::find_action(name) {
+            Some(action) => actions.push(action),
+            None => return Err(err(ParseMacroErrorKind::UnknownAction(name.to_string()))),
+        }
+    }
+    Ok(actions)
+}
+
+/// 导出宏时的选项
+#[derive(Copy, Clone, Debug)]
+pub struct MacroOptions {
+    /// 技能名称使用的客户端语言
+    pub locale: Locale,
+    /// 每个宏最多包含的行数，游戏内的宏最多为15行
+    pub max_lines: usize,
+    /// 是否在每个宏的第一行加入`/mlock`
+    pub lock: bool,
+    /// 若不为None，则在每个宏的最后一行加入`/echo`并播放指定编号的音效（1~16）
+    pub echo_sound: Option<u8>,
+}
+
+impl Default for MacroOptions {
+    fn default() -> Self {
+        Self {
+            locale: Locale::default(),
+            max_lines: 15,
+            lock: false,
+            echo_sound: None,
+        }
+    }
+}
+
+/// 宏导出错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportMacroError {
+    /// 序列中第`index`个技能是用于模拟失败结果的假技能，无法写入宏
+    FakeAction { index: usize, action: Actions },
+    /// 每个宏的行数限制过小，放不下任何技能
+    MaxLinesTooSmall,
+    /// 音效编号不在1~16之间
+    InvalidSound(u8),
+}
+
+impl Display for ExportMacroError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportMacroError::FakeAction { index, action } => {
+                write!(f, "action {:?} at {} is not a real action", action, index)
+            }
+            ExportMacroError::MaxLinesTooSmall => f.write_str("max lines too small"),
+            ExportMacroError::InvalidSound(se) => {
+                write!(f, "sound effect <se.{}> not in 1..=16", se)
+            }
+        }
+    }
+}
+
+impl Error for ExportMacroError {}
+
+/// 将技能序列导出为一个或多个可以直接粘贴进游戏的宏。
+///
+/// 每行会按照 [`Actions::wait_time`] 加上对应的`<wait.N>`，
+/// 当技能数超过单个宏的行数限制时会被拆分到多个宏中。
+/// `/echo`的提示文字与技能名称使用相同的语言。
+pub fn export(
+    actions: &[Actions],
+    options: &MacroOptions,
+) -> Result<Vec<String>, ExportMacroError> {
+    if let Some(se) = options.echo_sound.filter(|se| !(1..=16).contains(se)) {
+        return Err(ExportMacroError::InvalidSound(se));
+    }
+    let reserved = options.lock as usize + options.echo_sound.is_some() as usize;
+    let per_macro = options.max_lines.saturating_sub(reserved);
+    if per_macro == 0 {
+        return Err(ExportMacroError::MaxLinesTooSmall);
+    }
+    let mut lines = Vec::with_capacity(actions.len());
+    for (index, &action) in actions.iter().enumerate() {
+        let name = action
+            .name(options.locale)
+            .ok_or(ExportMacroError::FakeAction { index, action })?;
+        lines.push(if name.contains(char::is_whitespace) {
+            format!("/ac \"{}\" <wait.{}>", name, action.wait_time())
+        } else {
+            format!("/ac {} <wait.{}>", name, action.wait_time())
+        });
+    }
+    Ok(lines
+        .chunks(per_macro)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut m = String::new();
+            if options.lock {
+                m.push_str("/mlock\n");
+            }
+            for line in chunk {
+                m.push_str(line);
+                m.push('\n');
+            }
+            if let Some(se) = options.echo_sound {
+                m.push_str(&format!(
+                    "/echo {} <se.{}>\n",
+                    finished_message(options.locale, i + 1),
+                    se
+                ));
+            }
+            m
+        })
+        .collect())
+}
+
+/// 第`n`个宏执行完毕时的提示文字
+fn finished_message(locale: Locale, n: usize) -> String {
+    match locale {
+        Locale::En => format!("Macro #{} finished", n),
+        Locale::Ja => format!("マクロ{}完了", n),
+        Locale::De => format!("Makro {} abgeschlossen", n),
+        Locale::Fr => format!("Macro {} terminée", n),
+        Locale::Zh => format!("宏{}执行完毕", n),
+        Locale::Ko => format!("매크로 {} 완료", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        export, parse, ExportMacroError, MacroOptions, ParseMacroError, ParseMacroErrorKind,
+    };
+    use crate::{locale::Locale, Actions};
+
+    #[test]
+    fn parse_languages() {
+        let text = "/ac \"Basic Synthesis\" <wait.3>\n\
+                    /ac 作業 <wait.3>\n\
+                    /ac \"Motorisches Gedächtnis\" <wait.3>\n\
+                    /ac \"Travail de base\" <wait.3>\n\
+                    /ac 制作 <wait.3>\n\
+                    /ac \"byregot's blessing\"";
+        assert_eq!(
+            parse(text).unwrap(),
+            [
+                Actions::BasicSynthesis,
+                Actions::BasicSynthesis,
+                Actions::MuscleMemory,
+                Actions::BasicSynthesis,
+                Actions::BasicSynthesis,
+                Actions::ByregotsBlessing,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_ignored_lines() {
+        let text = "/mlock\n\n   \n/ac 观察 <wait.2>\n/wait 2\n/echo Macro #1 complete <se.1>";
+        assert_eq!(parse(text).unwrap(), [Actions::Observe]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("/ac 制作\n/ac \"Basic Synthesys\" <wait.3>"),
+            Err(ParseMacroError {
+                line: 2,
+                kind: ParseMacroErrorKind::UnknownAction("Basic Synthesys".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("/ac <wait.3>").unwrap_err().kind,
+            ParseMacroErrorKind::MissingAction
+        );
+        assert_eq!(
+            parse("\n/say hello").unwrap_err(),
+            ParseMacroError {
+                line: 2,
+                kind: ParseMacroErrorKind::UnknownCommand("/say".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn export_split() {
+        let actions = [Actions::MuscleMemory; 14];
+        let options = MacroOptions {
+            lock: true,
+            echo_sound: Some(1),
+            ..Default::default()
+        };
+        let macros = export(&actions, &options).unwrap();
+        assert_eq!(macros.len(), 2);
+        assert_eq!(macros[0].lines().count(), 15);
+        assert_eq!(macros[1].lines().count(), 3);
+        assert_eq!(
+            macros[1],
+            "/mlock\n/ac \"Muscle Memory\" <wait.3>\n/echo Macro #2 finished <se.1>\n"
+        );
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let actions = [
+            Actions::Reflect,
+            Actions::Manipulation,
+            Actions::Observe,
+            Actions::AdvancedTouch,
+            Actions::ByregotsBlessing,
+        ];
+        for locale in [
+            Locale::En,
+            Locale::Ja,
+            Locale::De,
+            Locale::Fr,
+            Locale::Zh,
+            Locale::Ko,
+        ] {
+            let options = MacroOptions {
+                locale,
+                ..Default::default()
+            };
+            let text = export(&actions, &options).unwrap().concat();
+            assert_eq!(parse(&text).unwrap(), actions);
+        }
+        let options = MacroOptions {
+            locale: Locale::Zh,
+            ..Default::default()
+        };
+        assert_eq!(
+            export(&actions[..2], &options).unwrap(),
+            ["/ac 闲静 <wait.3>\n/ac 掌握 <wait.2>\n"]
+        );
+    }
+
+    #[test]
+    fn export_wait_time() {
+        // 每个分类各取一个技能，等待时间与ActionInfo中的一致
+        let actions = [
+            (Actions::BasicSynthesis, 3),
+            (Actions::BasicTouch, 3),
+            (Actions::Innovation, 2),
+            (Actions::MastersMend, 3),
+            (Actions::TricksOfTheTrade, 2),
+        ];
+        let options = MacroOptions {
+            locale: Locale::Zh,
+            ..Default::default()
+        };
+        let text = export(&actions.map(|(a, _)| a), &options).unwrap().concat();
+        for (line, (action, wait)) in text.lines().zip(actions) {
+            assert_eq!(action.info(90).wait_time, wait);
+            assert!(line.ends_with(&format!("<wait.{}>", wait)), "{}", line);
+        }
+        assert_eq!(text.lines().count(), actions.len());
+    }
+
+    #[test]
+    fn export_echo() {
+        let options = MacroOptions {
+            locale: Locale::Zh,
+            echo_sound: Some(16),
+            ..Default::default()
+        };
+        assert_eq!(
+            export(&[Actions::Observe], &options).unwrap(),
+            ["/ac 观察 <wait.2>\n/echo 宏1执行完毕 <se.16>\n"]
+        );
+        for se in [0, 17] {
+            let options = MacroOptions {
+                echo_sound: Some(se),
+                ..Default::default()
+            };
+            assert_eq!(
+                export(&[Actions::Observe], &options),
+                Err(ExportMacroError::InvalidSound(se))
+            );
+        }
+    }
+
+    #[test]
+    fn export_fake_action() {
+        assert_eq!(
+            export(
+                &[Actions::HastyTouch, Actions::HastyTouchFail],
+                &MacroOptions::default()
+            ),
+            Err(ExportMacroError::FakeAction {
+                index: 1,
+                action: Actions::HastyTouchFail
+            })
+        );
+    }
+}
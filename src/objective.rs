@@ -0,0 +1,255 @@
+//! 制作的优化目标。
+//!
+//! [`Objective`] 对制作结束时的状态打分，分数越高越好。
+//! 求解器与评估器都接受任意的 [`Objective`]，因此搜索代码不需要了解每一种目标的含义。
+//! 内置的目标在制作未完成时都记为0分。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::objective::{Collectable, Objective};
+//! use ffxiv_crafting::{data, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 594,
+//!     specialist: false,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! let mut s = Status::new(attr, recipe);
+//! s.progress = recipe.difficulty;
+//! s.quality = 5000;
+//! assert_eq!(Collectable { threshold: 4800 }.score(&s), 1.0);
+//!
+//! // 闭包也可以作为目标使用
+//! let custom = |s: &Status| s.quality as f64 / 2.0;
+//! assert_eq!(custom.score(&s), 2500.0);
+//! ```
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use crate::{data, Status};
+
+/// 对制作结束时的状态打分的优化目标
+pub trait Objective {
+    /// 制作结束时的得分，越高越好
+    fn score(&self, s: &Status) -> f64;
+
+    /// 从未结束的状态`s`开始，若最终品质不超过`quality`，可能得到的最高分数。
+    ///
+    /// 求解器用它剪去不可能优于当前最优解的分支，因此返回值不能低于实际可达的分数。
+    /// 默认返回正无穷，即不做估计。
+    fn upper_bound(&self, s: &Status, quality: u32) -> f64 {
+        let _ = (s, quality);
+        f64::INFINITY
+    }
+
+    /// 得分是否与工次数（[`Status::step`]）有关。
+    ///
+    /// 返回false时，求解器可以合并只有工次数不同的状态，大幅减少需要求解的状态。
+    /// 默认返回true，即不做合并。
+    fn uses_step(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(&Status) -> f64> Objective for F {
+    fn score(&self, s: &Status) -> f64 {
+        self(s)
+    }
+}
+
+fn finished(s: &Status) -> bool {
+    s.progress >= s.recipe.difficulty
+}
+
+/// 最终品质最高，超过配方最高品质的部分不计
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MaxQuality;
+
+impl Objective for MaxQuality {
+    fn score(&self, s: &Status) -> f64 {
+        if !finished(s) {
+            return 0.0;
+        }
+        s.quality.min(s.recipe.quality) as f64
+    }
+
+    fn upper_bound(&self, s: &Status, quality: u32) -> f64 {
+        quality.min(s.recipe.quality) as f64
+    }
+
+    fn uses_step(&self) -> bool {
+        false
+    }
+}
+
+/// HQ概率（百分数）最高，没有品质的配方无法制作出HQ，总是记为0分
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct HighQuality;
+
+impl Objective for HighQuality {
+    fn score(&self, s: &Status) -> f64 {
+        if !finished(s) || s.recipe.quality == 0 {
+            return 0.0;
+        }
+        s.high_quality_probability().unwrap_or(100) as f64
+    }
+
+    fn upper_bound(&self, s: &Status, quality: u32) -> f64 {
+        if s.recipe.quality == 0 {
+            return 0.0;
+        }
+        let percent = quality.min(s.recipe.quality) * 100 / s.recipe.quality;
+        data::high_quality_table(percent).unwrap_or(100) as f64
+    }
+
+    fn uses_step(&self) -> bool {
+        false
+    }
+}
+
+/// 收藏品：品质达到`threshold`即为1分，否则为0分，超出的品质不计
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug)]
+pub struct Collectable {
+    pub threshold: u32,
+}
+
+impl Objective for Collectable {
+    fn score(&self, s: &Status) -> f64 {
+        (finished(s) && s.quality >= self.threshold) as u32 as f64
+    }
+
+    fn upper_bound(&self, _s: &Status, quality: u32) -> f64 {
+        (quality >= self.threshold) as u32 as f64
+    }
+
+    fn uses_step(&self) -> bool {
+        false
+    }
+}
+
+/// 保证完成制作，并使剩余的制作力最多。
+///
+/// 完成制作记为1分，再加上剩余制作力占制作力上限的比例。
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MinCraftPoints;
+
+impl Objective for MinCraftPoints {
+    fn score(&self, s: &Status) -> f64 {
+        if !finished(s) {
+            return 0.0;
+        }
+        1.0 + s.craft_points.max(0) as f64 / (s.attributes.craft_points.max(0) + 1) as f64
+    }
+
+    fn uses_step(&self) -> bool {
+        false
+    }
+}
+
+/// 完成制作并使用最少的工次，用于快速制作普通品质的道具。
+///
+/// 完成制作记为1分，再加上工次数加一的倒数。
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FewestSteps;
+
+impl Objective for FewestSteps {
+    fn score(&self, s: &Status) -> f64 {
+        if !finished(s) {
+            return 0.0;
+        }
+        1.0 + 1.0 / (s.step.max(0) + 1) as f64
+    }
+
+    fn upper_bound(&self, s: &Status, _quality: u32) -> f64 {
+        // 完成制作至少还需要一个工次
+        1.0 + 1.0 / (s.step.max(0) + 2) as f64
+    }
+}
+
+/// 在剩余至少`reserve`点制作力的前提下使品质最高，用于为之后的阶段保留制作力。
+/// 剩余制作力不足时记为0分。
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug)]
+pub struct KeepCraftPoints {
+    pub reserve: i32,
+}
+
+impl Objective for KeepCraftPoints {
+    fn score(&self, s: &Status) -> f64 {
+        if !finished(s) || s.craft_points < self.reserve {
+            return 0.0;
+        }
+        s.quality.min(s.recipe.quality) as f64
+    }
+
+    fn upper_bound(&self, s: &Status, quality: u32) -> f64 {
+        quality.min(s.recipe.quality) as f64
+    }
+
+    fn uses_step(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data, Attributes, Recipe};
+
+    #[test]
+    fn built_in() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 500,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        let mut s = Status::new(attr, recipe);
+        s.quality = recipe.quality + 100;
+        s.craft_points = 100;
+        s.step = 4;
+        let objectives: [&dyn Objective; 6] = [
+            &MaxQuality,
+            &HighQuality,
+            &Collectable { threshold: 1000 },
+            &MinCraftPoints,
+            &FewestSteps,
+            &KeepCraftPoints { reserve: 50 },
+        ];
+        for o in objectives {
+            assert_eq!(o.score(&s), 0.0);
+        }
+
+        s.progress = recipe.difficulty;
+        assert_eq!(MaxQuality.score(&s), recipe.quality as f64);
+        assert_eq!(HighQuality.score(&s), 100.0);
+        assert_eq!(Collectable { threshold: 1000 }.score(&s), 1.0);
+        assert!((MinCraftPoints.score(&s) - (1.0 + 100.0 / 501.0)).abs() < 1e-9);
+        assert_eq!(FewestSteps.score(&s), 1.2);
+        assert_eq!(
+            KeepCraftPoints { reserve: 50 }.score(&s),
+            recipe.quality as f64
+        );
+        assert_eq!(KeepCraftPoints { reserve: 150 }.score(&s), 0.0);
+
+        s.quality = 999;
+        assert_eq!(Collectable { threshold: 1000 }.score(&s), 0.0);
+        assert_eq!(Collectable { threshold: 1000 }.upper_bound(&s, 1000), 1.0);
+        assert!(HighQuality.upper_bound(&s, recipe.quality) >= HighQuality.score(&s));
+
+        // 没有品质的配方
+        s.recipe.quality = 0;
+        assert_eq!(HighQuality.score(&s), 0.0);
+        assert_eq!(HighQuality.upper_bound(&s, 0), 0.0);
+    }
+}
@@ -0,0 +1,165 @@
+//! 对有成功率的技能进行随机模拟。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::random::SplitMix64;
+//! use ffxiv_crafting::{data, Actions, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 3258,
+//!     control: 3340,
+//!     craft_points: 654,
+//!     specialist: false,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(590), 100, 100, 100);
+//! let mut s = Status::new(attr, recipe);
+//! let mut rng = SplitMix64::new(42);
+//! let (action, _outcome) = s.try_cast_action_random(Actions::HastyTouch, &mut rng).unwrap();
+//! assert!(matches!(action, Actions::HastyTouch | Actions::HastyTouchFail));
+//! ```
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Actions, CastActionError, CastOutcome, Status};
+
+/// 模拟中使用的随机数生成器，可以由使用者自行实现以接入其他随机数库。
+pub trait CraftRng {
+    /// 返回一个均匀分布的随机整数
+    fn next_u64(&mut self) -> u64;
+
+    /// 以`percent`%的概率返回true
+    fn roll(&mut self, percent: u8) -> bool {
+        self.next_u64() % 100 < percent as u64
+    }
+
+    /// 返回[0, 1)之间均匀分布的随机数
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<R: CraftRng + ?Sized> CraftRng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// 一个简单快速的随机数生成器，相同的种子总会得到相同的随机序列，便于复现模拟结果。
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+}
+
+impl CraftRng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Status {
+    /// 根据当前状态下的成功率随机决定技能是否成功。
+    /// 成功时返回技能本身，失败时返回对应的假技能。
+    pub fn roll_action<R: CraftRng + ?Sized>(&self, action: Actions, rng: &mut R) -> Actions {
+        match action.fail_action() {
+            Some(fail) if !rng.roll(self.success_rate(action)) => fail,
+            _ => action,
+        }
+    }
+
+    /// 检查并随机发动一次技能，返回实际发动的技能（或其失败时对应的假技能）以及技能造成的变化。
+    /// 若当前状态不允许发动该技能，则返回错误且不改变当前状态。
+    pub fn try_cast_action_random<R: CraftRng + ?Sized>(
+        &mut self,
+        action: Actions,
+        rng: &mut R,
+    ) -> Result<(Actions, CastOutcome), CastActionError> {
+        self.is_action_allowed(action)?;
+        let action = self.roll_action(action, rng);
+        Ok((action, self.cast_action_with_outcome(action)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CraftRng, SplitMix64};
+    use crate::{data, Actions, Attributes, Condition, Recipe, Status};
+
+    fn status() -> Status {
+        let attr = Attributes {
+            level: 100,
+            craftsmanship: 5668,
+            control: 5290,
+            craft_points: 615,
+            specialist: false,
+        };
+        Status::new(
+            attr,
+            Recipe::new(data::recipe_level_table(770), 100, 100, 100),
+        )
+    }
+
+    #[test]
+    fn seeded() {
+        let mut a = SplitMix64::new(7);
+        let mut b = SplitMix64::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let f = a.next_f64();
+        assert!((0.0..1.0).contains(&f));
+    }
+
+    #[test]
+    fn roll_rates() {
+        let mut rng = SplitMix64::new(1);
+        let mut s = status();
+        let n = 10000;
+        let count = |s: &Status, rng: &mut SplitMix64| {
+            (0..n)
+                .filter(|_| s.roll_action(Actions::HastyTouch, rng) == Actions::HastyTouch)
+                .count()
+        };
+        let normal = count(&s, &mut rng);
+        assert!((5700..6300).contains(&normal), "{}", normal);
+        s.condition = Condition::Centered;
+        let centered = count(&s, &mut rng);
+        assert!((8200..8800).contains(&centered), "{}", centered);
+        s.condition = Condition::Normal;
+        s.buffs.stellar_steady_hand = 1;
+        assert_eq!(count(&s, &mut rng), n);
+        assert_eq!(
+            s.roll_action(Actions::BasicTouch, &mut rng),
+            Actions::BasicTouch
+        );
+    }
+
+    #[test]
+    fn try_cast_random() {
+        let mut rng = SplitMix64::new(3);
+        let mut s = status();
+        let (action, outcome) = s
+            .try_cast_action_random(Actions::RapidSynthesis, &mut rng)
+            .unwrap();
+        match action {
+            Actions::RapidSynthesis => assert!(outcome.progress > 0),
+            Actions::RapidSynthesisFail => assert_eq!(outcome.progress, 0),
+            _ => unreachable!(),
+        }
+        assert_eq!(outcome.durability_lost, 10);
+        assert!(s
+            .try_cast_action_random(Actions::DaringTouch, &mut rng)
+            .is_err());
+    }
+}
@@ -0,0 +1,838 @@
+//! 可达品质的上界，以及完成进展所需预算的下界。
+//!
+//! 两者都通过松弛后的动态规划求得，并且总是偏向乐观：
+//!
+//! - 耐久按照最便宜的恢复方式折算为制作力，与制作力合并为统一的“预算”；
+//! - 加工与作业分别在两张表中计算，各自只推进自己使用的buff的回合数；
+//! - 观察、改革等技能的效果按照实际规则计算，但忽略大部分技能之间的互斥条件；
+//! - 当前工次按照实际的球色计算，之后的每个技能都按照 [`ConditionModel`] 中
+//!   之后可能出现的球色里对它最有利的一种计算，有成功率的技能按照成功计算。
+//!
+//! 因此这里的界对任何球色与成败的组合都成立，可以用于在界面上提前标出不可能达到的目标。
+//! 注意只要之后可能出现高品质或最高品质，秘诀就可以反复恢复制作力，
+//! 此时只要还能完成制作，品质的上界就是配方最高品质。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{data, solver, Attributes, Condition, ConditionModel, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 100,
+//!     specialist: false,
+//! };
+//! let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! recipe.durability = 35;
+//! // 只会出现通常与结实球色的配方
+//! recipe.conditions_flag = 1 << Condition::Normal as u16 | 1 << Condition::Sturdy as u16;
+//! let s = Status::new(attr, recipe);
+//! let model = ConditionModel::from_status(&s);
+//! // 100点制作力与35耐久不可能做出最高品质
+//! assert!(solver::bound::max_quality(&s, &model).unwrap() < recipe.quality);
+//! let craft_points = solver::bound::min_craft_points(&s, &model).unwrap();
+//! println!("至少需要{}点制作力推满进展", craft_points);
+//! ```
+use super::cast_normal;
+use crate::{Actions, Buffs, ComboStates, Condition, ConditionModel, Status};
+
+// buff的剩余回合数在起码状态下发动时最长，为通常的持续回合数加二，
+// 模拟器之外构造的更长的回合数按照这里的最大值计算
+const INNER_QUIET: usize = 11;
+const INNOVATION: usize = 7;
+const GREAT_STRIDES: usize = 6;
+const COMBO: usize = 4;
+const QUALITY_FLAGS: usize = 4;
+const QUALITY_STRIDE: usize = INNER_QUIET * INNOVATION * GREAT_STRIDES * COMBO * QUALITY_FLAGS;
+
+const VENERATION: usize = 7;
+const MUSCLE_MEMORY: usize = 8;
+const PROGRESS_FLAGS: usize = 4;
+const PROGRESS_STRIDE: usize = VENERATION * MUSCLE_MEMORY * PROGRESS_FLAGS;
+
+const UNKNOWN: u32 = u32::MAX;
+
+/// 按照`model`，从`status`开始能达到的最终品质的上界，不超过配方最高品质。
+/// 若可以确定无法完成制作，则返回None。
+///
+/// 需要对同一次制作的多个状态进行估计时，复用 [`Bounds`] 可以省去重复建表的开销。
+pub fn max_quality(status: &Status, model: &ConditionModel) -> Option<u32> {
+    Bounds::new(status, model).max_quality(status)
+}
+
+/// 按照`model`，从`status`开始推满进展至少需要消耗的制作力，
+/// 秘诀恢复的制作力计为负的消耗。若可以确定无法完成制作，则返回None。
+pub fn min_craft_points(status: &Status, model: &ConditionModel) -> Option<i32> {
+    Bounds::new(status, model).min_craft_points(status)
+}
+
+/// 可以在同一次制作的多个状态之间复用的估计器，内部的表格会随查询逐步建立
+pub struct Bounds {
+    level: u8,
+    base_synth: f32,
+    base_touch: f32,
+    /// 通过修理恢复每点耐久所需的制作力
+    rate: f64,
+    /// 俭约效果每持续一回合所需的制作力
+    waste_not: f64,
+    /// 有成功率的技能视为必定成功
+    steady_hand: bool,
+    /// 每个技能可以选择的球色效果，第一项总是通常
+    effects: Vec<Effect>,
+    /// 秘诀可以反复发动，制作力没有上限
+    unlimited: bool,
+    quality: Vec<u32>,
+    progress: Vec<u32>,
+}
+
+/// 一个工次的球色对技能的影响
+#[derive(Copy, Clone)]
+struct Effect {
+    touch: f64,
+    synth: f64,
+    /// 制作力消耗减半
+    pliant: bool,
+    /// 耐久消耗减半
+    sturdy: bool,
+    /// buff额外增加的回合数
+    extra_turns: u8,
+}
+
+impl Effect {
+    const NORMAL: Effect = Effect {
+        touch: 1.0,
+        synth: 1.0,
+        pliant: false,
+        sturdy: false,
+        extra_turns: 0,
+    };
+
+    /// 球色的效果，对所有技能都不优于通常的球色返回None
+    fn of(condition: Condition) -> Option<Effect> {
+        let normal = Self::NORMAL;
+        Some(match condition {
+            Condition::Good => Effect {
+                touch: 1.5,
+                ..normal
+            },
+            Condition::Excellent => Effect {
+                touch: 4.0,
+                ..normal
+            },
+            Condition::Sturdy | Condition::Robust => Effect {
+                sturdy: true,
+                ..normal
+            },
+            Condition::Pliant => Effect {
+                pliant: true,
+                ..normal
+            },
+            Condition::Malleable => Effect {
+                synth: 1.5,
+                ..normal
+            },
+            Condition::Primed => Effect {
+                extra_turns: 2,
+                ..normal
+            },
+            _ => return None,
+        })
+    }
+
+    fn craft_points(&self, craft_points: i32) -> i32 {
+        if self.pliant {
+            craft_points - craft_points / 2
+        } else {
+            craft_points
+        }
+    }
+
+    fn durability(&self, durability: u16) -> u16 {
+        if self.sturdy {
+            durability - durability / 2
+        } else {
+            durability
+        }
+    }
+}
+
+/// 加工表中的状态
+#[derive(Copy, Clone)]
+struct QualityState {
+    inner_quiet: u8,
+    innovation: u8,
+    great_strides: u8,
+    combo: u8,
+    quick_innovation: bool,
+    precise_touch: bool,
+}
+
+/// 专心致志的用途
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum HeartAndSoul {
+    Unavailable,
+    PreciseTouch,
+    IntensiveSynthesis,
+    TricksOfTheTrade,
+}
+
+/// 作业表中的状态
+#[derive(Copy, Clone)]
+struct ProgressState {
+    veneration: u8,
+    muscle_memory: u8,
+    intensive_synthesis: bool,
+    /// 耐久降为0的那一步如果推满了进展，制作依然成功，
+    /// 因此最后一个作业技能只需要剩余1点耐久
+    last: bool,
+}
+
+impl Bounds {
+    /// 为`status`所在的制作创建估计器，之后查询的状态应当与它使用相同的属性与配方，
+    /// 之后的工次可能出现`model`允许的任何球色
+    pub fn new(status: &Status, model: &ConditionModel) -> Self {
+        // 从当前球色出发，之后的工次可能出现的所有球色
+        let mut reachable = vec![];
+        let mut stack = vec![status.condition];
+        while let Some(c) = stack.pop() {
+            for &(next, _) in model.next(c) {
+                if !reachable.contains(&next) {
+                    reachable.push(next);
+                    stack.push(next);
+                }
+            }
+        }
+        let level = status.attributes.level;
+        let mut bounds = Self::with_conditions(status, &reachable, true);
+        bounds.unlimited = Actions::TricksOfTheTrade.info(level).unlock_level <= level
+            && reachable
+                .iter()
+                .any(|c| matches!(c, Condition::Good | Condition::Excellent));
+        bounds
+    }
+
+    /// 之后的工次都是通常球色时的估计器，用于白球求解器的剪枝
+    pub(crate) fn normal(status: &Status) -> Self {
+        let steady_hand =
+            status.limits.stellar_steady_hand_charged > 0 || status.buffs.stellar_steady_hand > 0;
+        Self::with_conditions(status, &[], steady_hand)
+    }
+
+    fn with_conditions(status: &Status, conditions: &[Condition], steady_hand: bool) -> Self {
+        let level = status.attributes.level;
+        let unlocked = |action: Actions| action.info(level).unlock_level <= level;
+        let mut effects = vec![Effect::NORMAL];
+        effects.extend(conditions.iter().filter_map(|&c| Effect::of(c)));
+        // 每次发动的制作力消耗与效果量之比，在所有球色中取最便宜的一种
+        let cheapest = |candidates: &[(Actions, u16, u16)]| {
+            let mut best = f64::INFINITY;
+            for &(action, base, per_turn) in candidates {
+                for e in &effects {
+                    let amount = base + per_turn * e.extra_turns as u16;
+                    if unlocked(action) && amount > 0 {
+                        let craft_points = e.craft_points(action.info(level).craft_points);
+                        best = best.min(craft_points as f64 / amount as f64);
+                    }
+                }
+            }
+            best
+        };
+        // 没有任何修理手段时耐久无法恢复，但仍然按照一个有限的代价估计
+        let rate = cheapest(&[
+            (Actions::MastersMend, 30, 0),
+            (Actions::Manipulation, 40, 5),
+            (Actions::ImmaculateMend, status.recipe.durability, 0),
+        ])
+        .min(3.0);
+        let waste_not = cheapest(&[(Actions::WasteNot, 4, 1), (Actions::WasteNotII, 8, 1)]);
+        Self {
+            level,
+            base_synth: status.caches.base_synth,
+            base_touch: status.caches.base_touch,
+            rate,
+            waste_not,
+            steady_hand,
+            effects,
+            unlimited: false,
+            quality: Vec::new(),
+            progress: Vec::new(),
+        }
+    }
+
+    /// 从`s`开始能达到的最终品质的上界，不超过配方最高品质。
+    /// 若可以确定无法完成制作，则返回None。
+    pub fn max_quality(&mut self, s: &Status) -> Option<u32> {
+        successors(s)
+            .into_iter()
+            .filter_map(|next| {
+                if next.progress >= next.recipe.difficulty {
+                    return Some(next.quality);
+                }
+                Some(next.quality + self.quality(&next)?)
+            })
+            .max()
+            .map(|q| q.min(s.recipe.quality))
+    }
+
+    /// 从`s`开始推满进展至少需要消耗的制作力，秘诀恢复的制作力计为负的消耗。
+    /// 若可以确定无法完成制作，则返回None。
+    pub fn min_craft_points(&mut self, s: &Status) -> Option<i32> {
+        successors(s)
+            .into_iter()
+            .filter_map(|next| {
+                let spent = s.craft_points - next.craft_points;
+                if next.progress >= next.recipe.difficulty {
+                    return Some(spent);
+                }
+                Some(spent + self.craft_points(&next)?)
+            })
+            .min()
+    }
+
+    /// 将当前的制作力与可用耐久合并为预算
+    fn budget(&self, s: &Status) -> u32 {
+        let unlocked = |action: Actions| action.info(self.level).unlock_level <= self.level;
+        let mut budget = s.craft_points.max(0) as f64
+            + (s.durability as u32 + s.buffs.manipulation as u32 * 5) as f64 * self.rate
+            + s.buffs.wast_not.max(s.buffs.wast_not_ii) as f64
+                * (10.0 * self.rate).min(self.waste_not);
+        if s.buffs.trained_perfection > 0
+            || (unlocked(Actions::TrainedPerfection) && s.limits.trained_perfection_used == 0)
+        {
+            budget += 20.0 * self.rate;
+        }
+        budget.ceil() as u32
+    }
+
+    fn heart_and_soul(&self, s: &Status) -> bool {
+        s.buffs.heart_and_soul > 0
+            || (s.attributes.specialist
+                && Actions::HeartAndSoul.info(self.level).unlock_level <= self.level
+                && s.limits.heart_and_soul_used == 0)
+    }
+
+    /// 技能的预算消耗，向下取整。
+    /// 耐久消耗取修理与在俭约效果下发动两者中较便宜的一种
+    fn cost(&self, craft_points: i32, durability: u16) -> u32 {
+        let repair = durability as f64 * self.rate;
+        let waste_not = (durability - durability / 2) as f64 * self.rate + self.waste_not;
+        (craft_points as f64 + repair.min(waste_not)).floor() as u32
+    }
+
+    /// 从`s`开始还能增加的品质的上界，第一工次不做估计。
+    /// 若可以确定无法完成制作，则返回None。
+    pub(crate) fn quality(&mut self, s: &Status) -> Option<u32> {
+        if s.step == 0 || self.unlimited {
+            return Some(s.recipe.quality);
+        }
+        if !self.heart_and_soul(s) {
+            return self.quality_with(s, HeartAndSoul::Unavailable);
+        }
+        // 专心致志只能生效一次，分别估计用于三个技能的情况
+        [
+            HeartAndSoul::PreciseTouch,
+            HeartAndSoul::IntensiveSynthesis,
+            HeartAndSoul::TricksOfTheTrade,
+        ]
+        .into_iter()
+        .map(|usage| self.quality_with(s, usage))
+        .max()
+        .flatten()
+    }
+
+    /// 从`s`开始完成剩余进展至少需要消耗的制作力，秘诀恢复的制作力计为负的消耗。
+    /// 若可以确定无法完成制作，则返回None。第一工次的状态不能用于估计。
+    pub(crate) fn craft_points(&mut self, s: &Status) -> Option<i32> {
+        debug_assert!(s.step > 0);
+        if self.unlimited {
+            // 制作结束时最多恢复到制作力上限
+            return Some(s.craft_points - s.craft_points.max(s.attributes.craft_points));
+        }
+        let usages: &[HeartAndSoul] = if self.heart_and_soul(s) {
+            &[
+                HeartAndSoul::Unavailable,
+                HeartAndSoul::IntensiveSynthesis,
+                HeartAndSoul::TricksOfTheTrade,
+            ]
+        } else {
+            &[HeartAndSoul::Unavailable]
+        };
+        let budget = self.budget(s);
+        // 预算中由耐久折算而来的部分
+        let credit = budget as i32 - s.craft_points.max(0);
+        usages
+            .iter()
+            .filter_map(|&usage| {
+                let tricks = if usage == HeartAndSoul::TricksOfTheTrade {
+                    20
+                } else {
+                    0
+                };
+                let required = self.required_budget(s, budget + tricks as u32, usage)?;
+                // 多余的耐久无法换回制作力
+                Some((required as i32 - credit).max(0) - tricks)
+            })
+            .min()
+    }
+
+    fn quality_with(&mut self, s: &Status, usage: HeartAndSoul) -> Option<u32> {
+        let mut budget = self.budget(s);
+        if usage == HeartAndSoul::TricksOfTheTrade {
+            budget += 20;
+        }
+        let required = self.required_budget(s, budget, usage)?;
+        let state = QualityState {
+            inner_quiet: s.buffs.inner_quiet.min(INNER_QUIET as u8 - 1),
+            innovation: s.buffs.innovation.min(INNOVATION as u8 - 1),
+            great_strides: s.buffs.great_strides.min(GREAT_STRIDES as u8 - 1),
+            combo: match s.combo {
+                None => 0,
+                Some(ComboStates::BasicTouched) => 1,
+                Some(ComboStates::StandardTouched) => 2,
+                Some(ComboStates::Observed) => 3,
+            },
+            quick_innovation: Actions::QuickInnovation.info(self.level).unlock_level <= self.level
+                && s.limits.quick_innovation_used == 0,
+            precise_touch: usage == HeartAndSoul::PreciseTouch,
+        };
+        Some(self.quality_table(budget - required, state))
+    }
+
+    /// 在`budget`以内完成剩余进展至少需要的预算，无法完成时返回None
+    fn required_budget(&mut self, s: &Status, budget: u32, usage: HeartAndSoul) -> Option<u32> {
+        let remaining = s.recipe.difficulty.saturating_sub(s.progress) as u32;
+        let state = ProgressState {
+            veneration: s.buffs.veneration.min(VENERATION as u8 - 1),
+            muscle_memory: s.buffs.muscle_memory.min(MUSCLE_MEMORY as u8 - 1),
+            intensive_synthesis: usage == HeartAndSoul::IntensiveSynthesis,
+            last: true,
+        };
+        if self.progress_table(budget, state) < remaining {
+            return None;
+        }
+        // 可推动的进展随预算单调不减，二分查找满足条件的最小预算
+        let (mut lo, mut hi) = (0, budget);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.progress_table(mid, state) >= remaining {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        Some(lo)
+    }
+
+    fn quality_table(&mut self, budget: u32, st: QualityState) -> u32 {
+        let index = budget as usize * QUALITY_STRIDE
+            + ((((st.inner_quiet as usize * INNOVATION + st.innovation as usize) * GREAT_STRIDES
+                + st.great_strides as usize)
+                * COMBO
+                + st.combo as usize)
+                * QUALITY_FLAGS
+                + st.quick_innovation as usize * 2
+                + st.precise_touch as usize);
+        if index >= self.quality.len() {
+            self.quality
+                .resize((budget as usize + 1) * QUALITY_STRIDE, UNKNOWN);
+        }
+        if self.quality[index] != UNKNOWN {
+            return self.quality[index];
+        }
+
+        let level = self.level;
+        let mut best = 0;
+        for i in 0..self.effects.len() {
+            let e = self.effects[i];
+            if st.quick_innovation && st.innovation == 0 {
+                let next = QualityState {
+                    innovation: 1 + e.extra_turns,
+                    quick_innovation: false,
+                    ..st
+                };
+                best = best.max(self.quality_table(budget, next));
+            }
+            for action in [
+                Actions::BasicTouch,
+                Actions::StandardTouch,
+                Actions::AdvancedTouch,
+                Actions::HastyTouch,
+                Actions::DaringTouch,
+                Actions::PrudentTouch,
+                Actions::PreparatoryTouch,
+                Actions::RefinedTouch,
+                Actions::PreciseTouch,
+                Actions::TrainedFinesse,
+                Actions::ByregotsBlessing,
+                Actions::DelicateSynthesis,
+                Actions::Innovation,
+                Actions::GreatStrides,
+                Actions::Observe,
+            ] {
+                let info = action.info(level);
+                if info.unlock_level > level {
+                    continue;
+                }
+                let mut craft_points = info.craft_points;
+                let mut durability = info.durability;
+                let mut next = QualityState {
+                    combo: 0,
+                    innovation: st.innovation.saturating_sub(1),
+                    great_strides: st.great_strides.saturating_sub(1),
+                    ..st
+                };
+                let mut inner_quiet = 1;
+                let mut efficiency = info.quality_efficiency as f64 / 100.0;
+                let turns = e.extra_turns + 1;
+                match action {
+                    Actions::BasicTouch => next.combo = 1,
+                    Actions::StandardTouch if st.combo == 1 => {
+                        craft_points = 18;
+                        next.combo = 2;
+                    }
+                    Actions::AdvancedTouch if st.combo >= 2 => craft_points = 18,
+                    Actions::HastyTouch | Actions::DaringTouch if !self.steady_hand => continue,
+                    Actions::PreparatoryTouch => inner_quiet = 2,
+                    Actions::RefinedTouch if st.combo == 1 => inner_quiet = 2,
+                    Actions::PreciseTouch if !st.precise_touch => continue,
+                    Actions::PreciseTouch => {
+                        inner_quiet = 2;
+                        next.precise_touch = false;
+                    }
+                    Actions::TrainedFinesse if st.inner_quiet < 10 => continue,
+                    Actions::TrainedFinesse => inner_quiet = 0,
+                    Actions::ByregotsBlessing if st.inner_quiet == 0 => continue,
+                    Actions::ByregotsBlessing => {
+                        efficiency = (efficiency + st.inner_quiet as f64 * 0.2).min(3.0);
+                        inner_quiet = -(st.inner_quiet as i8);
+                    }
+                    // 作业部分的耐久消耗计入作业表
+                    Actions::DelicateSynthesis => durability = 0,
+                    Actions::Innovation => next.innovation = st.innovation.max(4 + turns) - 1,
+                    Actions::GreatStrides => {
+                        next.great_strides = st.great_strides.max(3 + turns) - 1
+                    }
+                    Actions::Observe => next.combo = 3,
+                    _ => {}
+                }
+                let cost = self.cost(e.craft_points(craft_points), e.durability(durability));
+                if cost > budget {
+                    continue;
+                }
+                let mut gain = 0;
+                if efficiency > 0.0 {
+                    let buffs = Buffs {
+                        inner_quiet: st.inner_quiet,
+                        innovation: st.innovation,
+                        great_strides: st.great_strides,
+                        ..Default::default()
+                    };
+                    gain =
+                        (self.base_touch as f64 * buffs.touch(efficiency) * e.touch) as f32 as u32;
+                    next.great_strides = 0;
+                    next.inner_quiet = st.inner_quiet.saturating_add_signed(inner_quiet).min(10);
+                }
+                best = best.max(gain + self.quality_table(budget - cost, next));
+            }
+        }
+        self.quality[index] = best;
+        best
+    }
+
+    fn progress_table(&mut self, budget: u32, st: ProgressState) -> u32 {
+        let index = budget as usize * PROGRESS_STRIDE
+            + ((st.veneration as usize * MUSCLE_MEMORY + st.muscle_memory as usize)
+                * PROGRESS_FLAGS
+                + st.intensive_synthesis as usize * 2
+                + st.last as usize);
+        if index >= self.progress.len() {
+            self.progress
+                .resize((budget as usize + 1) * PROGRESS_STRIDE, UNKNOWN);
+        }
+        if self.progress[index] != UNKNOWN {
+            return self.progress[index];
+        }
+
+        let level = self.level;
+        let mut best = 0;
+        for i in 0..self.effects.len() {
+            let e = self.effects[i];
+            for action in [
+                Actions::BasicSynthesis,
+                Actions::CarefulSynthesis,
+                Actions::PrudentSynthesis,
+                Actions::Groundwork,
+                Actions::DelicateSynthesis,
+                Actions::IntensiveSynthesis,
+                Actions::RapidSynthesis,
+                Actions::Veneration,
+            ] {
+                let info = action.info(level);
+                if info.unlock_level > level {
+                    continue;
+                }
+                let mut craft_points = info.craft_points;
+                let mut next = ProgressState {
+                    veneration: st.veneration.saturating_sub(1),
+                    muscle_memory: st.muscle_memory.saturating_sub(1),
+                    ..st
+                };
+                match action {
+                    // 加工部分的制作力消耗计入加工表
+                    Actions::DelicateSynthesis => craft_points = 0,
+                    Actions::IntensiveSynthesis if !st.intensive_synthesis => continue,
+                    Actions::IntensiveSynthesis => next.intensive_synthesis = false,
+                    Actions::RapidSynthesis if !self.steady_hand => continue,
+                    Actions::Veneration => {
+                        next.veneration = st.veneration.max(5 + e.extra_turns) - 1
+                    }
+                    _ => {}
+                }
+                let craft_points = e.craft_points(craft_points);
+                let mut gain = 0;
+                if info.progress_efficiency > 0 {
+                    let buffs = Buffs {
+                        veneration: st.veneration,
+                        muscle_memory: st.muscle_memory,
+                        ..Default::default()
+                    };
+                    let efficiency = info.progress_efficiency as f64 / 100.0;
+                    gain = (self.base_synth as f64 * buffs.synthesis(efficiency) * e.synth) as f32
+                        as u16 as u32;
+                    next.muscle_memory = 0;
+                    if st.last && self.cost(craft_points, 1) <= budget {
+                        best = best.max(gain);
+                    }
+                }
+                let cost = self.cost(craft_points, e.durability(info.durability));
+                if cost > budget {
+                    continue;
+                }
+                best = best.max(gain + self.progress_table(budget - cost, next));
+            }
+        }
+        self.progress[index] = best;
+        best
+    }
+}
+
+/// 展开当前工次所有可以发动的技能，有成功率的技能按照成功计算。
+/// 这样当前工次可以按照实际的球色计算，只能在第一工次发动的技能也不会被遗漏。
+/// 设计变动与专心致志不消耗工次，发动后仍停留在第一工次的状态会继续展开，
+/// 设计变动之后的球色也按照通常计算。最终确认不会使估计更乐观，因此不做展开。
+/// `s`已经完成时只返回它自身，耗尽耐久而失败的结果会被排除。
+fn successors(s: &Status) -> Vec<Status> {
+    if s.progress >= s.recipe.difficulty {
+        return vec![s.clone()];
+    }
+    if s.durability == 0 {
+        return Vec::new();
+    }
+    Actions::all()
+        .iter()
+        .filter(|&&action| {
+            !action.info(s.attributes.level).fake
+                && action != Actions::FinalAppraisal
+                && s.is_action_allowed(action).is_ok()
+        })
+        .map(|&action| cast_normal(s, action))
+        .flat_map(|next| match next.step {
+            0 => successors(&next),
+            _ if next.progress < next.recipe.difficulty && next.durability == 0 => Vec::new(),
+            _ => vec![next],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{max_quality, min_craft_points, Bounds};
+    use crate::solver::tests::status;
+    use crate::solver::{finish, quality};
+    use crate::{data, Actions, Condition, ConditionModel, Recipe, StateKey, Status};
+
+    /// 穷举所有技能与之后所有可能的球色，得到(最高品质, 完成时最多剩余的制作力)
+    fn brute_force(
+        s: &Status,
+        model: &ConditionModel,
+        memo: &mut HashMap<StateKey, Option<(u32, i32)>>,
+    ) -> Option<(u32, i32)> {
+        if s.progress >= s.recipe.difficulty {
+            return Some((s.quality.min(s.recipe.quality), s.craft_points));
+        }
+        if s.durability == 0 {
+            return None;
+        }
+        if let Some(&result) = memo.get(&s.key()) {
+            return result;
+        }
+        let mut best: Option<(u32, i32)> = None;
+        for &action in Actions::all() {
+            if action.info(s.attributes.level).fake
+                || matches!(
+                    action,
+                    Actions::FinalAppraisal | Actions::CarefulObservation
+                )
+                || s.is_action_allowed(action).is_err()
+            {
+                continue;
+            }
+            let mut next = s.clone();
+            next.cast_action(action);
+            let conditions: Vec<_> = if next.step == s.step {
+                vec![s.condition]
+            } else {
+                model.next(s.condition).iter().map(|&(c, _)| c).collect()
+            };
+            for c in conditions {
+                next.condition = c;
+                if let Some((quality, craft_points)) = brute_force(&next, model, memo) {
+                    let (q, cp) = best.unwrap_or((0, i32::MIN));
+                    best = Some((q.max(quality), cp.max(craft_points)));
+                }
+            }
+        }
+        memo.insert(s.key(), best);
+        best
+    }
+
+    #[test]
+    fn admissible() {
+        for (craft_points, durability) in [(0, 40), (24, 20), (40, 20), (45, 30), (120, 40)] {
+            for condition in [
+                Condition::Normal,
+                Condition::Good,
+                Condition::Excellent,
+                Condition::Poor,
+                Condition::Pliant,
+                Condition::Sturdy,
+            ] {
+                let mut s = status(craft_points, durability);
+                s.condition = condition;
+                let mut bounds = Bounds::normal(&s);
+                let best = quality::solve(&s).map(|x| x.status.quality);
+                let upper = bounds.max_quality(&s);
+                assert!(upper >= best, "{:?} {:?}", upper, best);
+
+                let least = finish::solve(&s, finish::Goal::LeastCraftPoints)
+                    .map(|x| s.craft_points - x.status.craft_points);
+                let lower = bounds.min_craft_points(&s);
+                if let Some(least) = least {
+                    assert!(lower.is_some_and(|x| x <= least), "{:?} {}", lower, least);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_conditions() {
+        // 没有高品质的配方中，结实、简约、长持续、起码等球色仍然可能使结果更好
+        let flag = [
+            Condition::Normal,
+            Condition::Centered,
+            Condition::Sturdy,
+            Condition::Pliant,
+            Condition::Malleable,
+            Condition::Primed,
+            Condition::Robust,
+        ]
+        .iter()
+        .fold(0, |flag, &c| flag | 1 << c as u16);
+        for (craft_points, durability) in [(24, 20), (40, 15)] {
+            let mut s = status(craft_points, durability);
+            s.recipe.conditions_flag = flag;
+            s.cast_action(Actions::MuscleMemory);
+            let model = ConditionModel::from_status(&s);
+            let best = brute_force(&s, &model, &mut HashMap::new());
+            let upper = max_quality(&s, &model);
+            let lower = min_craft_points(&s, &model);
+            let (quality, remaining) = best.unwrap();
+            assert!(upper >= Some(quality), "{:?} {}", upper, quality);
+            let spent = s.craft_points - remaining;
+            assert!(lower.is_some_and(|x| x <= spent), "{:?} {}", lower, spent);
+            // 白球的估计低于实际可能达到的品质
+            let normal = Bounds::normal(&s).max_quality(&s);
+            assert!(normal < Some(quality));
+        }
+
+        // 可能出现高品质时，秘诀可以反复恢复制作力
+        let s = status(0, 20);
+        let model = ConditionModel::from_status(&s);
+        assert_eq!(max_quality(&s, &model), Some(s.recipe.quality));
+        assert!(min_craft_points(&s, &model).unwrap() <= 0);
+    }
+
+    #[test]
+    fn max_quality_reachable() {
+        let max_quality = |s: &Status| Bounds::normal(s).max_quality(s);
+        let mut s = status(594, 70);
+        assert_eq!(max_quality(&s), Some(s.recipe.quality));
+        s.craft_points = 100;
+        assert!(max_quality(&s) < Some(s.recipe.quality));
+
+        // 工匠的神速技巧可以直接达到最高品质
+        let recipe = Recipe::new(data::recipe_level_table(400), 100, 100, 100);
+        let mut s = Status::new(status(250, 40).attributes, recipe);
+        assert!(s.is_action_allowed(Actions::TrainedEye).is_ok());
+        assert_eq!(max_quality(&s), Some(s.recipe.quality));
+
+        s.progress = s.recipe.difficulty;
+        let model = ConditionModel::from_status(&s);
+        assert_eq!(max_quality(&s), Some(0));
+        assert_eq!(min_craft_points(&s, &model), Some(0));
+        s.progress = 0;
+        s.durability = 0;
+        assert_eq!(max_quality(&s), None);
+        assert_eq!(super::max_quality(&s, &model), None);
+    }
+
+    #[test]
+    fn normal_only() {
+        let max_quality = |s: &Status| Bounds::normal(s).max_quality(s);
+        // 当前工次的球色按实际计算
+        let mut s = status(60, 30);
+        s.cast_action(Actions::MuscleMemory);
+        let normal = max_quality(&s).unwrap();
+        s.condition = Condition::Excellent;
+        assert!(max_quality(&s).unwrap() > normal);
+
+        // 之后的工次出现最高品质时，实际品质可以超过白球的上界，但不超过考虑球色的上界
+        let s = status(60, 30);
+        let normal = max_quality(&s).unwrap();
+        let solution = quality::solve(&s).unwrap();
+        let mut lucky = s.clone();
+        for &action in &solution.actions {
+            lucky.condition = Condition::Excellent;
+            lucky.cast_action(action);
+        }
+        assert!(lucky.progress >= s.recipe.difficulty);
+        assert!(lucky.quality > normal, "{} {}", lucky.quality, normal);
+        let model = ConditionModel::from_status(&s);
+        assert!(super::max_quality(&s, &model).unwrap() >= lucky.quality);
+    }
+
+    #[test]
+    fn primed() {
+        // 起码状态下buff的持续回合数比通常多两回合
+        for action in [
+            Actions::Veneration,
+            Actions::Innovation,
+            Actions::GreatStrides,
+        ] {
+            let mut s = status(120, 40);
+            s.cast_action(Actions::MuscleMemory);
+            s.condition = Condition::Primed;
+            s.cast_action(action);
+            let mut bounds = Bounds::normal(&s);
+            let upper = bounds.max_quality(&s).unwrap();
+            let best = quality::solve(&s).unwrap().status.quality;
+            assert!(upper >= best, "{:?} {} {}", action, upper, best);
+            let least = finish::solve(&s, finish::Goal::LeastCraftPoints).unwrap();
+            let lower = bounds.min_craft_points(&s).unwrap();
+            assert!(lower <= s.craft_points - least.status.craft_points);
+        }
+    }
+}
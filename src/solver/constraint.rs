@@ -0,0 +1,249 @@
+//! 求解时需要满足的约束条件。
+//!
+//! 玩家可能无法或不愿使用某些技能，或者需要把技能序列放进固定行数的宏中，
+//! 这些要求都可以通过 [`Constraints`] 交给求解器。
+//! 找不到满足全部约束的技能序列时，求解器会通过 [`Violation`] 指出是哪一项约束无法满足。
+//!
+//! 给出技能序列的 [`sequence`](super::sequence) 与 [`pareto`](super::pareto) 使用全部约束；
+//! [`policy`](super::policy) 与 [`mcts`](super::mcts) 在每个状态下逐步决策，
+//! 只使用其中对技能的限制（`allowed`、`banned`与`no_risky`）。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::objective::MaxQuality;
+//! use ffxiv_crafting::solver::constraint::{Constraints, Violation};
+//! use ffxiv_crafting::{data, solver, Actions, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 40,
+//!     specialist: true,
+//! };
+//! let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! recipe.durability = 20;
+//! let s = Status::new(attr, recipe);
+//! let constraints = Constraints {
+//!     prefix: vec![Actions::MuscleMemory],
+//!     banned: vec![Actions::TrainedEye],
+//!     min_craft_points: 1,
+//!     ..Default::default()
+//! };
+//! let solution = solver::sequence::solve_with(&s, &MaxQuality, &constraints).unwrap();
+//! assert_eq!(solution.actions[0], Actions::MuscleMemory);
+//! assert!(constraints.check(&s, &solution.actions).is_ok());
+//!
+//! // 两步之内无法完成制作
+//! let constraints = Constraints {
+//!     max_steps: Some(2),
+//!     ..Default::default()
+//! };
+//! let result = solver::sequence::solve_with(&s, &MaxQuality, &constraints);
+//! assert_eq!(result.unwrap_err(), Violation::TooManySteps);
+//! ```
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use super::cast_normal;
+use crate::{Actions, CastActionError, Status};
+
+/// 技能序列需要满足的约束条件，默认不做任何限制
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct Constraints {
+    /// 只允许使用这些技能，None表示不限制
+    pub allowed: Option<Vec<Actions>>,
+    /// 禁止使用的技能
+    pub banned: Vec<Actions>,
+    /// 技能序列必须以这些技能开始
+    pub prefix: Vec<Actions>,
+    /// 技能数的上限，包括前缀在内
+    pub max_steps: Option<usize>,
+    /// 制作结束时至少剩余的制作力
+    pub min_craft_points: i32,
+    /// 制作结束时至少剩余的耐久
+    pub min_durability: u16,
+    /// 禁止使用当前状态下成功率不足100%的技能，即没有稳手效果时的仓促、冒进与高速制作
+    pub no_risky: bool,
+}
+
+/// 无法满足的约束条件
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// 第`index`个技能不在允许使用的范围内
+    ActionNotPermitted { index: usize, action: Actions },
+    /// 第`index`个技能与要求的前缀不同，或技能序列比前缀短
+    PrefixMismatch { index: usize },
+    /// 第`index`个技能无法发动
+    IllegalAction {
+        index: usize,
+        action: Actions,
+        error: CastActionError,
+    },
+    /// 只使用允许的技能时无法完成制作
+    ActionsRestricted,
+    /// 技能数超过上限
+    TooManySteps,
+    /// 制作结束时剩余的制作力不足
+    CraftPointsBelowMinimum,
+    /// 制作结束时剩余的耐久不足
+    DurabilityBelowMinimum,
+    /// 无法完成制作
+    Unfinished,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::ActionNotPermitted { index, action } => {
+                write!(f, "action {:?} at {} is not permitted", action, index)
+            }
+            Violation::PrefixMismatch { index } => {
+                write!(f, "action at {} does not match the required prefix", index)
+            }
+            Violation::IllegalAction {
+                index,
+                action,
+                error,
+            } => write!(f, "action {:?} at {}: {}", action, index, error),
+            Violation::ActionsRestricted => {
+                f.write_str("cannot finish with only the permitted actions")
+            }
+            Violation::TooManySteps => f.write_str("too many steps"),
+            Violation::CraftPointsBelowMinimum => f.write_str("not enough craft points left"),
+            Violation::DurabilityBelowMinimum => f.write_str("not enough durability left"),
+            Violation::Unfinished => f.write_str("cannot finish the craft"),
+        }
+    }
+}
+
+impl Error for Violation {}
+
+impl Constraints {
+    /// 状态`s`下是否允许使用技能`action`
+    pub fn permits(&self, s: &Status, action: Actions) -> bool {
+        self.allowed.as_ref().is_none_or(|a| a.contains(&action))
+            && !self.banned.contains(&action)
+            && !(self.no_risky && s.success_rate(action) < 100)
+    }
+
+    /// 检查从`status`开始发动`actions`是否满足全部约束，之后的工次都按照通常球色计算。
+    /// 返回发现的第一个问题。
+    pub fn check(&self, status: &Status, actions: &[Actions]) -> Result<(), Violation> {
+        if actions.len() < self.prefix.len() {
+            return Err(Violation::PrefixMismatch {
+                index: actions.len(),
+            });
+        }
+        let mut s = status.clone();
+        for (index, &action) in actions.iter().enumerate() {
+            if self.prefix.get(index).is_some_and(|&p| p != action) {
+                return Err(Violation::PrefixMismatch { index });
+            }
+            s = self.cast(&s, index, action)?;
+        }
+        self.check_finished(&s, actions.len())
+    }
+
+    /// 依次发动前缀中的技能，返回之后的状态
+    pub(crate) fn apply_prefix(&self, status: &Status) -> Result<Status, Violation> {
+        let mut s = status.clone();
+        for (index, &action) in self.prefix.iter().enumerate() {
+            s = self.cast(&s, index, action)?;
+        }
+        if self.max_steps.is_some_and(|n| self.prefix.len() > n) {
+            return Err(Violation::TooManySteps);
+        }
+        Ok(s)
+    }
+
+    /// 前缀之后还可以发动的技能数
+    pub(crate) fn remaining_steps(&self) -> Option<usize> {
+        self.max_steps.map(|n| n.saturating_sub(self.prefix.len()))
+    }
+
+    fn cast(&self, s: &Status, index: usize, action: Actions) -> Result<Status, Violation> {
+        if !self.permits(s, action) {
+            return Err(Violation::ActionNotPermitted { index, action });
+        }
+        if let Err(error) = s.is_action_allowed(action) {
+            return Err(Violation::IllegalAction {
+                index,
+                action,
+                error,
+            });
+        }
+        Ok(cast_normal(s, action))
+    }
+
+    fn check_finished(&self, s: &Status, steps: usize) -> Result<(), Violation> {
+        if self.max_steps.is_some_and(|n| steps > n) {
+            Err(Violation::TooManySteps)
+        } else if s.progress < s.recipe.difficulty {
+            Err(Violation::Unfinished)
+        } else if s.craft_points < self.min_craft_points {
+            Err(Violation::CraftPointsBelowMinimum)
+        } else if s.durability < self.min_durability {
+            Err(Violation::DurabilityBelowMinimum)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constraints, Violation};
+    use crate::{data, Actions, Attributes, CastActionError, Recipe, Status};
+
+    #[test]
+    fn check() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 100,
+            specialist: false,
+        };
+        let s = Status::new(
+            attr,
+            Recipe::new(data::recipe_level_table(560), 100, 100, 100),
+        );
+        let constraints = Constraints {
+            banned: vec![Actions::BasicTouch],
+            prefix: vec![Actions::MuscleMemory],
+            no_risky: true,
+            ..Default::default()
+        };
+        assert!(!constraints.permits(&s, Actions::RapidSynthesis));
+        assert!(constraints.permits(&s, Actions::Groundwork));
+        assert_eq!(
+            constraints.check(&s, &[Actions::Reflect]),
+            Err(Violation::PrefixMismatch { index: 0 })
+        );
+        assert_eq!(
+            constraints.check(&s, &[Actions::MuscleMemory, Actions::BasicTouch]),
+            Err(Violation::ActionNotPermitted {
+                index: 1,
+                action: Actions::BasicTouch
+            })
+        );
+        assert_eq!(
+            constraints.check(&s, &[Actions::MuscleMemory, Actions::MuscleMemory]),
+            Err(Violation::IllegalAction {
+                index: 1,
+                action: Actions::MuscleMemory,
+                error: CastActionError::OnlyAllowedInFirstStep
+            })
+        );
+        assert_eq!(
+            constraints.check(&s, &[Actions::MuscleMemory]),
+            Err(Violation::Unfinished)
+        );
+    }
+}
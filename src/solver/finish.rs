@@ -0,0 +1,297 @@
+//! 求解推满进展的收尾技能序列，以及判断当前状态是否还能完成制作。
+//!
+//! 之后的工次都按照通常球色计算，推动进展时会考虑崇敬、坚信的剩余效果、
+//! 掌握的耐久恢复以及耐久不足时坯料制作效率减半等规则。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{data, solver, Actions, Attributes, Recipe, Status};
+//! use solver::finish::Goal;
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 594,
+//!     specialist: false,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(580), 100, 100, 100);
+//! let mut s = Status::new(attr, recipe);
+//! s.cast_action(Actions::MuscleMemory);
+//! s.cast_action(Actions::Manipulation);
+//! assert!(solver::finish::can_finish(&s));
+//!
+//! let solution = solver::finish::solve(&s, Goal::FewestSteps).unwrap();
+//! assert!(solution.status.progress >= recipe.difficulty);
+//! println!("{:?}", solution.actions);
+//! ```
+use std::cell::RefCell;
+
+use super::bound::Bounds;
+use super::constraint::Constraints;
+use super::{cast_normal, deterministic_actions, sequence, Solution};
+use crate::objective::Objective;
+use crate::{Actions, Status};
+
+/// 收尾序列的优化目标
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// 工次最少，工次相同时制作力消耗最少
+    FewestSteps,
+    /// 制作力消耗最少，制作力消耗相同时工次最少
+    LeastCraftPoints,
+}
+
+/// 求解从`status`开始推满进展的技能序列，无法完成制作时返回None。
+///
+/// 求得的序列只包含与推动进展有关的技能，按照`goal`最优。
+/// 其中工次按 [`Status::step`] 计算，不消耗工次的技能（如专心致志）不计入在内；
+/// 制作力按初始与最终制作力的差值计算，秘诀恢复的制作力会抵消消耗。
+pub fn solve(status: &Status, goal: Goal) -> Option<Solution> {
+    let objective = Finish {
+        goal,
+        bounds: RefCell::new(Bounds::normal(status)),
+    };
+    sequence::search(status, &objective, &progress_only())
+}
+
+/// 判断从`status`开始是否还能推满进展。
+///
+/// 绝大多数情况可以由简单的贪心收尾或制作力下界直接判断，
+/// 只有两者都无法确定时才进行完整的搜索。
+pub fn can_finish(status: &Status) -> bool {
+    if status.progress >= status.recipe.difficulty {
+        return true;
+    }
+    if status.durability == 0 {
+        return false;
+    }
+    if greedy(status) {
+        return true;
+    }
+    match Bounds::normal(status).min_craft_points(status) {
+        Some(craft_points) if craft_points <= status.craft_points => {}
+        _ => return false,
+    }
+    solve(status, Goal::FewestSteps).is_some()
+}
+
+/// 贪心收尾：能直接推满进展时结束，否则发动制作，耐久不足时先修理。
+/// 返回true时一定可以完成制作，返回false时不能说明无法完成。
+fn greedy(status: &Status) -> bool {
+    let constraints = progress_only();
+    let mut s = status.clone();
+    loop {
+        if s.progress >= s.recipe.difficulty {
+            return true;
+        }
+        if s.durability == 0 {
+            return false;
+        }
+        let finished = deterministic_actions(&s, &constraints)
+            .any(|a| cast_normal(&s, a).progress >= s.recipe.difficulty);
+        if finished {
+            return true;
+        }
+        let synthesis = cast_normal(&s, Actions::BasicSynthesis);
+        if synthesis.durability > 0 {
+            s = synthesis;
+            continue;
+        }
+        // 修理都需要消耗制作力，因此循环一定会结束
+        let repair = [
+            Actions::MastersMend,
+            Actions::Manipulation,
+            Actions::ImmaculateMend,
+        ]
+        .into_iter()
+        .filter(|&a| s.is_action_allowed(a).is_ok())
+        .map(|a| cast_normal(&s, a))
+        .find(|next| {
+            next.durability > s.durability || next.buffs.manipulation > s.buffs.manipulation
+        });
+        match repair {
+            Some(next) => s = next,
+            None => return false,
+        }
+    }
+}
+
+/// 分数中工次与制作力的权重之比，保证主要目标总是优先于次要目标
+const WEIGHT: f64 = 10000.0;
+
+/// 把[`Goal`]换算为分数，交给 [`sequence`] 的搜索求解
+struct Finish {
+    goal: Goal,
+    bounds: RefCell<Bounds>,
+}
+
+impl Finish {
+    fn value(&self, step: i32, craft_points: i32) -> f64 {
+        let (step, craft_points) = (step as f64, craft_points as f64);
+        match self.goal {
+            Goal::FewestSteps => craft_points - step * WEIGHT,
+            Goal::LeastCraftPoints => craft_points * WEIGHT - step,
+        }
+    }
+}
+
+impl Objective for Finish {
+    fn score(&self, s: &Status) -> f64 {
+        if s.progress < s.recipe.difficulty {
+            return f64::NEG_INFINITY;
+        }
+        self.value(s.step, s.craft_points)
+    }
+
+    fn upper_bound(&self, s: &Status, _quality: u32) -> f64 {
+        // 第一工次的状态不能用于估计制作力
+        let craft_points = if s.step == 0 {
+            s.craft_points.max(s.attributes.craft_points)
+        } else {
+            match self.bounds.borrow_mut().craft_points(s) {
+                Some(need) => s.craft_points - need,
+                None => return f64::NEG_INFINITY,
+            }
+        };
+        // 完成制作至少还需要一个工次
+        self.value(s.step + 1, craft_points)
+    }
+}
+
+/// 禁用所有只影响品质的技能
+fn progress_only() -> Constraints {
+    Constraints {
+        banned: Actions::all()
+            .iter()
+            .copied()
+            .filter(|&a| !helps_progress(a))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// 可能有助于推满进展的技能，排除只影响品质的技能
+fn helps_progress(action: Actions) -> bool {
+    !matches!(
+        action,
+        Actions::BasicTouch
+            | Actions::StandardTouch
+            | Actions::AdvancedTouch
+            | Actions::HastyTouch
+            | Actions::DaringTouch
+            | Actions::PrudentTouch
+            | Actions::PreparatoryTouch
+            | Actions::RefinedTouch
+            | Actions::PreciseTouch
+            | Actions::TrainedFinesse
+            | Actions::ByregotsBlessing
+            | Actions::Reflect
+            | Actions::TrainedEye
+            | Actions::Innovation
+            | Actions::GreatStrides
+            | Actions::QuickInnovation
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{can_finish, progress_only, solve, Finish, Goal};
+    use crate::objective::Objective;
+    use crate::solver::bound::Bounds;
+    use crate::solver::tests::brute_force;
+    use crate::{data, Actions, Attributes, Recipe, Status};
+    use std::cell::RefCell;
+
+    fn status(craft_points: i32, durability: u16, progress: u16) -> Status {
+        let mut s = crate::solver::tests::status(craft_points, durability);
+        s.step = 1;
+        s.progress = progress;
+        s
+    }
+
+    #[test]
+    fn optimal() {
+        let difficulty = status(0, 0, 0).recipe.difficulty;
+        for (craft_points, durability, progress) in [
+            (0, 20, difficulty / 2),
+            (30, 15, difficulty / 3),
+            (40, 10, difficulty / 2),
+            (60, 20, difficulty / 3),
+            (24, 25, 0),
+        ] {
+            let s = status(craft_points, durability, progress);
+            for goal in [Goal::FewestSteps, Goal::LeastCraftPoints] {
+                let solution = solve(&s, goal);
+                let objective = Finish {
+                    goal,
+                    bounds: RefCell::new(Bounds::normal(&s)),
+                };
+                let score = solution.as_ref().map(|x| objective.score(&x.status));
+                let best = brute_force(&s, &objective, &progress_only());
+                assert_eq!(score, best, "{:?} {:?}", s, goal);
+                assert_eq!(can_finish(&s), score.is_some());
+                if let Some(x) = solution {
+                    assert!(x.status.progress >= s.recipe.difficulty);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn endgame() {
+        // 高等级配方的初始状态，贪心收尾即可判断
+        let attr = Attributes {
+            level: 100,
+            craftsmanship: 5400,
+            control: 5200,
+            craft_points: 700,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(690), 100, 100, 100);
+        let s = Status::new(attr, recipe);
+        assert!(can_finish(&s));
+
+        // 制作力与耐久都不足以推满进展
+        let mut s = Status::new(
+            Attributes {
+                craft_points: 0,
+                ..attr
+            },
+            recipe,
+        );
+        s.durability = 10;
+        assert!(!can_finish(&s));
+    }
+
+    #[test]
+    fn buffs() {
+        // 坚信与崇敬的剩余效果都应该被利用
+        let mut s = status(60, 40, 0);
+        s.step = 0;
+        s.limits.heart_and_soul_used = 1;
+        s.cast_action(Actions::MuscleMemory);
+        s.cast_action(Actions::Veneration);
+        let solution = solve(&s, Goal::FewestSteps).unwrap();
+        assert_eq!(solution.actions[0], Actions::Groundwork);
+        assert!(solution.status.progress >= s.recipe.difficulty);
+
+        // 耐久不足时坯料制作的效率减半，需要先修理耐久
+        let base = s.caches.base_synth as u16;
+        let mut s = status(110, 40, s.recipe.difficulty - base * 3);
+        s.durability = 5;
+        s.limits.heart_and_soul_used = 1;
+        let solution = solve(&s, Goal::FewestSteps).unwrap();
+        assert_eq!(
+            solution.actions,
+            [Actions::MastersMend, Actions::Groundwork]
+        );
+    }
+
+    #[test]
+    fn unfinishable() {
+        let s = status(0, 10, 0);
+        assert!(!can_finish(&s));
+        assert!(solve(&s, Goal::LeastCraftPoints).is_none());
+    }
+}
@@ -0,0 +1,422 @@
+//! 用于实时制作的蒙特卡洛树搜索。
+//!
+//! 搜索按照 [`ConditionModel`] 随机产生球色，并按成功率随机决定技能是否成功，
+//! 在给定的迭代次数或时间内给出当前最好的技能。
+//! 实际发动技能并观察到结果后，可以调用 [`Mcts::advance`] 保留对应的子树继续搜索，
+//! 因此适合在每个工次调用一次的实时助手。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::random::SplitMix64;
+//! use ffxiv_crafting::{data, solver, Attributes, ConditionModel, Recipe, Status};
+//! use solver::mcts::{Budget, Mcts};
+//! use ffxiv_crafting::objective::MaxQuality;
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 594,
+//!     specialist: false,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! let mut s = Status::new(attr, recipe);
+//! let model = ConditionModel::from_status(&s);
+//! let mut rng = SplitMix64::new(1);
+//!
+//! let mut mcts = Mcts::new(&s, MaxQuality, 42);
+//! while !s.is_finished() {
+//!     let result = mcts.search(Budget::Iterations(200));
+//!     let Some(action) = result.action else { break };
+//!     s.try_step_random(action, &model, &mut rng).unwrap();
+//!     mcts.advance(action, &s);
+//! }
+//! ```
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::candidate_actions;
+use super::constraint::Constraints;
+use crate::objective::Objective;
+use crate::random::SplitMix64;
+use crate::{Actions, ConditionModel, StateKey, Status};
+
+/// 一次搜索的预算
+#[derive(Copy, Clone, Debug)]
+pub enum Budget {
+    /// 迭代次数
+    Iterations(usize),
+    /// 搜索时间
+    Time(Duration),
+}
+
+/// 根节点下某个技能的统计数据
+#[derive(Copy, Clone, Debug)]
+pub struct ActionStats {
+    pub action: Actions,
+    /// 该技能被搜索的次数
+    pub visits: u32,
+    /// 搜索中发动该技能后所得结果的平均值，与 [`Objective::score`] 的单位相同
+    pub mean: f64,
+}
+
+/// 一次搜索的结果
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    /// 被搜索次数最多的技能，制作已经结束或没有可以发动的技能时为None
+    pub action: Option<Actions>,
+    /// 根节点下每个技能的统计数据，按搜索次数从多到少排列
+    pub stats: Vec<ActionStats>,
+    /// 本次搜索进行的迭代次数
+    pub iterations: usize,
+}
+
+struct Node {
+    status: Status,
+    visits: u32,
+    edges: Vec<Edge>,
+    /// 尚未尝试过的技能
+    untried: Vec<Actions>,
+}
+
+struct Edge {
+    action: Actions,
+    visits: u32,
+    total: f64,
+    /// 发动技能后已经出现过的结果
+    outcomes: HashMap<StateKey, usize>,
+}
+
+/// 蒙特卡洛树搜索规划器
+pub struct Mcts<O> {
+    objective: O,
+    model: ConditionModel,
+    rng: SplitMix64,
+    exploration: f64,
+    constraints: Constraints,
+    nodes: Vec<Node>,
+    root: usize,
+    /// 搜索中出现过的最低与最高分数，用于将分数归一化
+    range: (f64, f64),
+}
+
+impl<O: Objective> Mcts<O> {
+    /// UCT公式中探索项的默认系数，分数按搜索中出现过的范围归一化到0到1之间后使用
+    pub const DEFAULT_EXPLORATION: f64 = 0.5;
+
+    /// 从`status`开始搜索，使`objective`的期望最高。相同的`seed`总会得到相同的结果。
+    pub fn new(status: &Status, objective: O, seed: u64) -> Self {
+        let mut mcts = Self {
+            objective,
+            model: ConditionModel::from_status(status),
+            rng: SplitMix64::new(seed),
+            exploration: Self::DEFAULT_EXPLORATION,
+            constraints: Constraints::default(),
+            nodes: Vec::new(),
+            root: 0,
+            range: (f64::INFINITY, f64::NEG_INFINITY),
+        };
+        mcts.root = mcts.add_node(status.clone());
+        mcts
+    }
+
+    /// 设置UCT公式中探索项的系数
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// 设置搜索中可以使用的技能，只使用`constraints`中对技能的限制。
+    /// 之前的搜索结果会被丢弃
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        let status = self.status().clone();
+        self.constraints = constraints;
+        self.nodes.clear();
+        self.range = (f64::INFINITY, f64::NEG_INFINITY);
+        self.root = self.add_node(status);
+        self
+    }
+
+    /// 当前根节点的状态
+    pub fn status(&self) -> &Status {
+        &self.nodes[self.root].status
+    }
+
+    /// 在预算内继续搜索，返回当前最好的技能以及根节点下各技能的统计数据。
+    /// 多次调用会在之前的搜索结果上继续累积。
+    pub fn search(&mut self, budget: Budget) -> SearchResult {
+        let start = Instant::now();
+        let mut iterations = 0;
+        while match budget {
+            Budget::Iterations(n) => iterations < n,
+            Budget::Time(d) => iterations == 0 || start.elapsed() < d,
+        } {
+            if self.nodes[self.root].status.is_finished() {
+                break;
+            }
+            self.iterate();
+            iterations += 1;
+        }
+
+        let mut stats: Vec<ActionStats> = self.nodes[self.root]
+            .edges
+            .iter()
+            .map(|e| ActionStats {
+                action: e.action,
+                visits: e.visits,
+                mean: if e.visits > 0 {
+                    e.total / e.visits as f64
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+        stats.sort_by(|a, b| b.visits.cmp(&a.visits).then(b.mean.total_cmp(&a.mean)));
+        SearchResult {
+            action: stats.first().map(|s| s.action),
+            stats,
+            iterations,
+        }
+    }
+
+    /// 实际发动`action`并观察到结果`observed`后，将根节点移动到对应的子树。
+    /// 若搜索中没有出现过该结果，则从`observed`重新开始搜索。
+    pub fn advance(&mut self, action: Actions, observed: &Status) {
+        let child = self.nodes[self.root]
+            .edges
+            .iter()
+            .find(|e| e.action == action)
+            .and_then(|e| e.outcomes.get(&observed.key()).copied());
+        let mut old: Vec<Option<Node>> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.root = match child {
+            Some(child) => extract(&mut old, child, &mut self.nodes),
+            None => self.add_node(observed.clone()),
+        };
+    }
+
+    fn new_node(&self, status: Status) -> Node {
+        let mut untried: Vec<Actions> = if status.is_finished() {
+            Vec::new()
+        } else {
+            candidate_actions(&status, &self.constraints).collect()
+        };
+        untried.reverse();
+        Node {
+            status,
+            visits: 0,
+            edges: Vec::new(),
+            untried,
+        }
+    }
+
+    fn add_node(&mut self, status: Status) -> usize {
+        let node = self.new_node(status);
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// 进行一次选择、扩展、模拟与回传
+    fn iterate(&mut self) {
+        let mut path = Vec::new();
+        let mut index = self.root;
+        let value = loop {
+            let node = &mut self.nodes[index];
+            if node.status.is_finished() || (node.edges.is_empty() && node.untried.is_empty()) {
+                break self.objective.score(&node.status);
+            }
+            let edge = match node.untried.pop() {
+                Some(action) => {
+                    node.edges.push(Edge {
+                        action,
+                        visits: 0,
+                        total: 0.0,
+                        outcomes: HashMap::new(),
+                    });
+                    node.edges.len() - 1
+                }
+                None => self.select(index),
+            };
+            path.push((index, edge));
+
+            let node = &self.nodes[index];
+            let mut next = node.status.clone();
+            next.try_step_random(node.edges[edge].action, &self.model, &mut self.rng)
+                .unwrap();
+            match node.edges[edge].outcomes.get(&next.key()) {
+                Some(&child) => index = child,
+                None => {
+                    let key = next.key();
+                    let child = self.add_node(next.clone());
+                    self.nodes[index].edges[edge].outcomes.insert(key, child);
+                    path.push((child, usize::MAX));
+                    break self.rollout(next);
+                }
+            }
+        };
+        self.range = (self.range.0.min(value), self.range.1.max(value));
+        for (index, edge) in path {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            if let Some(edge) = node.edges.get_mut(edge) {
+                edge.visits += 1;
+                edge.total += value;
+            }
+        }
+    }
+
+    /// 按照UCT公式选择技能
+    fn select(&self, index: usize) -> usize {
+        let node = &self.nodes[index];
+        let ln = (node.visits.max(1) as f64).ln();
+        let (low, high) = self.range;
+        let width = if high > low { high - low } else { 1.0 };
+        let uct = |e: &Edge| {
+            let mean = e.total / e.visits as f64;
+            (mean - low) / width + self.exploration * (ln / e.visits as f64).sqrt()
+        };
+        (0..node.edges.len())
+            .max_by(|&a, &b| uct(&node.edges[a]).total_cmp(&uct(&node.edges[b])))
+            .unwrap()
+    }
+
+    /// 从`s`开始尽快推满进展，估计该状态的价值。
+    ///
+    /// 能一步完成时选择其中消耗制作力最少的技能，
+    /// 否则选择不会使耐久耗尽的技能中推动进展最多的一个。
+    fn rollout(&mut self, mut s: Status) -> f64 {
+        while !s.is_finished() {
+            // (完成制作, 推动后的进展, 剩余制作力)
+            let mut best: Option<((u8, u16, i32), Actions)> = None;
+            for action in candidate_actions(&s, &self.constraints) {
+                if s.success_rate(action) < 100 {
+                    continue;
+                }
+                let mut next = s.clone();
+                next.cast_action(action);
+                if next.progress == s.progress {
+                    continue;
+                }
+                let key = if next.progress >= next.recipe.difficulty {
+                    (2, 0, next.craft_points)
+                } else {
+                    ((next.durability > 0) as u8, next.progress, 0)
+                };
+                if best.is_none_or(|(b, _)| key > b) {
+                    best = Some((key, action));
+                }
+            }
+            let Some((_, action)) = best else {
+                break;
+            };
+            s.try_step_random(action, &self.model, &mut self.rng)
+                .unwrap();
+        }
+        self.objective.score(&s)
+    }
+}
+
+/// 将`old`中以`index`为根的子树移动到`nodes`中，返回其新的位置
+fn extract(old: &mut [Option<Node>], index: usize, nodes: &mut Vec<Node>) -> usize {
+    let mut node = old[index].take().unwrap();
+    let mut edges = std::mem::take(&mut node.edges);
+    let new = nodes.len();
+    nodes.push(node);
+    for edge in &mut edges {
+        for child in edge.outcomes.values_mut() {
+            *child = extract(old, *child, nodes);
+        }
+    }
+    nodes[new].edges = edges;
+    new
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Budget, Mcts};
+    use crate::objective::{HighQuality, MaxQuality};
+    use crate::solver::constraint::Constraints;
+    use crate::{data, Actions, Attributes, Condition, Recipe, Status};
+
+    fn status() -> Status {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points: 300,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        Status::new(attr, recipe)
+    }
+
+    #[test]
+    fn reproducible() {
+        let s = status();
+        let a = Mcts::new(&s, MaxQuality, 7).search(Budget::Iterations(500));
+        let b = Mcts::new(&s, MaxQuality, 7).search(Budget::Iterations(500));
+        assert_eq!(a.iterations, 500);
+        assert_eq!(a.action, b.action);
+        for (x, y) in a.stats.iter().zip(&b.stats) {
+            assert_eq!((x.action, x.visits), (y.action, y.visits));
+        }
+        assert_eq!(a.stats.iter().map(|x| x.visits).sum::<u32>(), 500);
+    }
+
+    #[test]
+    fn reuse_tree() {
+        // 只有通常球色时，必定成功的技能只有一种结果，一定出现在搜索树中
+        let mut s = status();
+        s.recipe.conditions_flag = 1 << Condition::Normal as u16;
+        let mut mcts = Mcts::new(&s, MaxQuality, 1);
+        mcts.search(Budget::Iterations(2000));
+        let action = Actions::Reflect;
+        s.cast_action(action);
+        mcts.advance(action, &s);
+        assert_eq!(mcts.status().key(), s.key());
+        let result = mcts.search(Budget::Iterations(10));
+        assert!(result.stats.iter().map(|x| x.visits).sum::<u32>() > 10);
+
+        // 没有出现过的结果则重新开始搜索
+        s.quality += 1;
+        mcts.advance(Actions::BasicTouch, &s);
+        assert_eq!(mcts.status().key(), s.key());
+        let result = mcts.search(Budget::Time(Duration::from_millis(20)));
+        assert!(result.iterations > 0);
+        assert_eq!(
+            result.stats.iter().map(|x| x.visits).sum::<u32>(),
+            result.iterations as u32
+        );
+    }
+
+    #[test]
+    fn finished() {
+        let mut s = status();
+        s.progress = s.recipe.difficulty;
+        let result = Mcts::new(&s, HighQuality, 1).search(Budget::Iterations(10));
+        assert!(result.action.is_none());
+        assert_eq!(result.iterations, 0);
+    }
+
+    #[test]
+    fn constraints() {
+        let s = status();
+        let constraints = Constraints {
+            banned: vec![Actions::Reflect],
+            no_risky: true,
+            ..Default::default()
+        };
+        let mut mcts = Mcts::new(&s, MaxQuality, 3).with_constraints(constraints.clone());
+        let result = mcts.search(Budget::Iterations(500));
+        assert!(!result.stats.is_empty());
+        for x in &result.stats {
+            assert!(constraints.permits(&s, x.action));
+        }
+        let mut found = result.stats.iter().map(|x| x.action);
+        assert!(!found.any(|a| a == Actions::HastyTouch || a == Actions::Reflect));
+    }
+}
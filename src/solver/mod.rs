@@ -0,0 +1,121 @@
+//! 技能序列求解器。
+//!
+//! 求解器直接调用本crate的模拟器推演每一步，因此求得的技能序列一定符合模拟器的规则。
+//!
+//! - [`bound`]：快速估计可达品质的上界与推满进展所需制作力的下界
+//! - [`quality`]：求解白球（通常）状态下能完成制作且品质最高的技能序列
+//! - [`finish`]：求解推满进展的收尾技能序列，判断当前状态是否还能完成制作
+//! - [`sequence`]：求解白球状态下使任意 [`Objective`](crate::objective::Objective) 最高的技能序列
+//! - [`pareto`]：求解品质、剩余制作力、技能数与成功率之间的帕累托前沿
+//! - [`policy`]：求解随球色变化、使目标期望最高的策略
+//! - [`constraint`]：求解时需要满足的约束条件，如禁用的技能、固定的起手与技能数上限
+//! - [`mcts`]：在有限的时间内搜索当前最好的技能，用于实时制作
+use crate::{Actions, Condition, Status};
+use constraint::Constraints;
+
+pub mod bound;
+pub mod constraint;
+pub mod finish;
+pub mod mcts;
+pub mod pareto;
+pub mod policy;
+pub mod quality;
+pub mod sequence;
+
+/// 求解器给出的技能序列
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// 技能序列
+    pub actions: Vec<Actions>,
+    /// 按顺序发动全部技能后的状态
+    pub status: Status,
+}
+
+/// 在球色固定为通常时，当前状态下值得尝试且`constraints`允许使用的技能。
+///
+/// 只考虑必定成功的技能，
+/// 并排除在通常球色下不会带来任何收益的最终确认与设计变动。
+/// 专心致志的效果会一直保留到被使用为止，因此只在其生效后立即使用，
+/// 以免同一个序列以不同的顺序被重复搜索。
+fn deterministic_actions<'a>(
+    s: &'a Status,
+    constraints: &'a Constraints,
+) -> impl Iterator<Item = Actions> + 'a {
+    Actions::all().iter().copied().filter(move |&action| {
+        !action.info(s.attributes.level).fake
+            && !matches!(
+                action,
+                Actions::FinalAppraisal | Actions::CarefulObservation
+            )
+            && (s.buffs.heart_and_soul == 0
+                || matches!(
+                    action,
+                    Actions::PreciseTouch | Actions::IntensiveSynthesis | Actions::TricksOfTheTrade
+                ))
+            && s.success_rate(action) == 100
+            && s.is_action_allowed(action).is_ok()
+            && constraints.permits(s, action)
+    })
+}
+
+/// 在球色与成功率随机时，当前状态下值得尝试且`constraints`允许使用的技能。
+/// 求解器总是可以选择不推满进展的技能，因此排除最终确认。
+fn candidate_actions<'a>(
+    s: &'a Status,
+    constraints: &'a Constraints,
+) -> impl Iterator<Item = Actions> + 'a {
+    Actions::all().iter().copied().filter(move |&action| {
+        !action.info(s.attributes.level).fake
+            && action != Actions::FinalAppraisal
+            && s.is_action_allowed(action).is_ok()
+            && constraints.permits(s, action)
+    })
+}
+
+/// 发动技能，并假定之后的工次都是通常球色
+fn cast_normal(s: &Status, action: Actions) -> Status {
+    let mut next = s.clone();
+    next.cast_action(action);
+    next.condition = Condition::Normal;
+    next
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::constraint::Constraints;
+    use super::{cast_normal, deterministic_actions};
+    use crate::objective::Objective;
+    use crate::{data, Attributes, Recipe, Status};
+
+    /// 各个求解器的测试共用的初始状态
+    pub(crate) fn status(craft_points: i32, durability: u16) -> Status {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points,
+            specialist: true,
+        };
+        let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        recipe.durability = durability;
+        Status::new(attr, recipe)
+    }
+
+    /// 穷举所有满足`constraints`中技能限制的必定成功的技能序列，
+    /// 返回能完成制作的序列中`objective`的最高分数
+    pub(crate) fn brute_force<O: Objective + ?Sized>(
+        s: &Status,
+        objective: &O,
+        constraints: &Constraints,
+    ) -> Option<f64> {
+        if s.progress >= s.recipe.difficulty {
+            return Some(objective.score(s));
+        }
+        if s.durability == 0 {
+            return None;
+        }
+        deterministic_actions(s, constraints)
+            .filter_map(|action| brute_force(&cast_normal(s, action), objective, constraints))
+            .max_by(f64::total_cmp)
+    }
+}
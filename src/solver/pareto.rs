@@ -0,0 +1,315 @@
+//! 求解白球（通常）状态下完成制作的技能序列的帕累托前沿。
+//!
+//! 当最高品质无法达到时，品质、剩余制作力、技能数与成功率之间往往需要取舍，
+//! 前沿中的每一个点都不会在全部四项上同时劣于另一个点。
+//! 除必定成功的技能外，搜索还会考虑仓促、冒进与高速制作，
+//! 此时技能序列按照全部技能都成功来计算，成功率为各技能成功率的乘积。
+//!
+//! 每个子状态都需要保存完整的前沿，计算量与内存占用都比 [`quality`](super::quality) 大得多，
+//! 适合在制作力不多的局面下使用。
+//! [`solve_with`] 还可以附加 [`Constraints`] 限制可以使用的技能、技能数等。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{data, solver, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 40,
+//!     specialist: true,
+//! };
+//! let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! recipe.durability = 20;
+//! let s = Status::new(attr, recipe);
+//! for point in solver::pareto::solve(&s) {
+//!     println!(
+//!         "品质 {} 剩余制作力 {} 技能数 {} 成功率 {:.2} {:?}",
+//!         point.quality, point.craft_points, point.steps, point.probability, point.actions
+//!     );
+//! }
+//! ```
+use std::collections::HashMap;
+
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use super::constraint::{Constraints, Violation};
+use super::{cast_normal, deterministic_actions};
+use crate::{Actions, StateKey, Status};
+
+/// 帕累托前沿上的一个点
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct ParetoPoint {
+    /// 最终品质
+    pub quality: u32,
+    /// 剩余的制作力
+    pub craft_points: i32,
+    /// 技能数
+    pub steps: usize,
+    /// 全部技能都成功的概率
+    pub probability: f64,
+    /// 技能序列
+    pub actions: Vec<Actions>,
+}
+
+/// 求解从`status`开始，在之后的每个工次都是通常球色的前提下，
+/// 完成制作的技能序列在最终品质、剩余制作力、技能数与成功率上的帕累托前沿。
+///
+/// 超过配方最高品质的部分不计。结果按品质从高到低排列，无法完成制作时返回空的列表。
+pub fn solve(status: &Status) -> Vec<ParetoPoint> {
+    solve_with(status, &Constraints::default()).unwrap_or_default()
+}
+
+/// 求解从`status`开始满足`constraints`中全部约束的技能序列的帕累托前沿，
+/// 之后的工次都按照通常球色计算。
+///
+/// 前缀中的技能按照全部成功计算，其成功率同样计入每个点的成功率中。
+/// 前缀本身无法满足时返回前缀中的问题，找不到满足全部约束的技能序列时返回空的列表。
+pub fn solve_with(
+    status: &Status,
+    constraints: &Constraints,
+) -> Result<Vec<ParetoPoint>, Violation> {
+    let start = constraints.apply_prefix(status)?;
+    let mut probability = 1.0;
+    let mut replay = status.clone();
+    for &action in &constraints.prefix {
+        probability *= replay.success_rate(action) as f64 / 100.0;
+        replay = cast_normal(&replay, action);
+    }
+
+    let mut search = Search {
+        max_quality: status.recipe.quality,
+        constraints,
+        frontiers: Vec::new(),
+        memo: HashMap::new(),
+    };
+    let root = search.frontier(&normalize(start.clone()));
+    let max_steps = constraints.remaining_steps().unwrap_or(usize::MAX);
+
+    let points: Vec<ParetoPoint> = search.frontiers[root]
+        .iter()
+        .filter(|e| e.steps as usize <= max_steps)
+        .map(|e| {
+            let mut actions = constraints.prefix.clone();
+            let mut entry = e;
+            while let Some(action) = entry.action {
+                actions.push(action);
+                entry = &search.frontiers[entry.next.0][entry.next.1];
+            }
+            ParetoPoint {
+                quality: (start.quality + e.quality).min(status.recipe.quality),
+                craft_points: e.craft_points,
+                steps: constraints.prefix.len() + e.steps as usize,
+                probability: probability * e.probability,
+                actions,
+            }
+        })
+        .collect();
+    // 加上已有的品质后，部分点可能因超出最高品质而被其他点支配
+    Ok(prune(points, |p| {
+        (p.quality, p.craft_points, p.steps as u32, p.probability)
+    }))
+}
+
+/// 子状态前沿中的一个点
+struct Entry {
+    /// 之后增加的品质，不超过配方最高品质
+    quality: u32,
+    /// 制作结束时剩余的制作力
+    craft_points: i32,
+    /// 之后的技能数
+    steps: u32,
+    probability: f64,
+    /// 第一个技能，制作已经结束时为None
+    action: Option<Actions>,
+    /// 发动该技能后的子状态的前沿及其中的点
+    next: (usize, usize),
+}
+
+struct Search<'a> {
+    max_quality: u32,
+    constraints: &'a Constraints,
+    frontiers: Vec<Vec<Entry>>,
+    memo: HashMap<StateKey, usize>,
+}
+
+impl Search<'_> {
+    /// 计算子状态的前沿，返回其在`frontiers`中的位置
+    fn frontier(&mut self, s: &Status) -> usize {
+        let key = s.key();
+        if let Some(&index) = self.memo.get(&key) {
+            return index;
+        }
+        let mut entries = Vec::new();
+        if s.progress >= s.recipe.difficulty {
+            // 剩余的制作力与耐久不足时，该子状态没有满足约束的点
+            if s.craft_points >= self.constraints.min_craft_points
+                && s.durability >= self.constraints.min_durability
+            {
+                entries.push(Entry {
+                    quality: 0,
+                    craft_points: s.craft_points,
+                    steps: 0,
+                    probability: 1.0,
+                    action: None,
+                    next: (0, 0),
+                });
+            }
+        } else if s.durability > 0 {
+            let risky = [
+                Actions::HastyTouch,
+                Actions::DaringTouch,
+                Actions::RapidSynthesis,
+            ]
+            .into_iter()
+            .filter(|&a| {
+                s.success_rate(a) < 100
+                    && s.is_action_allowed(a).is_ok()
+                    && self.constraints.permits(s, a)
+            });
+            let actions: Vec<Actions> = deterministic_actions(s, self.constraints)
+                .chain(risky)
+                .collect();
+            for action in actions {
+                let probability = s.success_rate(action) as f64 / 100.0;
+                let next = cast_normal(s, action);
+                let gain = next.quality;
+                let child = self.frontier(&normalize(next));
+                for (i, e) in self.frontiers[child].iter().enumerate() {
+                    entries.push(Entry {
+                        quality: (gain + e.quality).min(self.max_quality),
+                        craft_points: e.craft_points,
+                        steps: e.steps + 1,
+                        probability: probability * e.probability,
+                        action: Some(action),
+                        next: (child, i),
+                    });
+                }
+            }
+        }
+        let index = self.frontiers.len();
+        self.frontiers.push(prune(entries, |e| {
+            (e.quality, e.craft_points, e.steps, e.probability)
+        }));
+        self.memo.insert(key, index);
+        index
+    }
+}
+
+/// 帕累托前沿的比较依据：(品质, 剩余制作力, 技能数, 成功率)，除技能数外越大越好
+type Key = (u32, i32, u32, f64);
+
+/// 去掉被支配的点
+fn prune<T>(mut items: Vec<T>, key: impl Fn(&T) -> Key) -> Vec<T> {
+    items.sort_by(|a, b| {
+        let (a, b) = (key(a), key(b));
+        b.0.cmp(&a.0)
+            .then(b.1.cmp(&a.1))
+            .then(a.2.cmp(&b.2))
+            .then(b.3.total_cmp(&a.3))
+    });
+    let mut frontier: Vec<T> = Vec::new();
+    for item in items {
+        // 排序后，能支配item的点一定排在它的前面
+        if !frontier.iter().any(|f| dominates(key(f), key(&item))) {
+            frontier.push(item);
+        }
+    }
+    frontier
+}
+
+/// `a`是否在每一项上都不劣于`b`
+fn dominates(a: Key, b: Key) -> bool {
+    a.0 >= b.0 && a.1 >= b.1 && a.2 <= b.2 && a.3 >= b.3
+}
+
+/// 剥离与之后的决策无关的信息：已有的品质，以及除“是否为第一工次”外的步数
+fn normalize(mut s: Status) -> Status {
+    s.quality = 0;
+    s.step = s.step.min(1);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve_with};
+    use crate::solver::constraint::{Constraints, Violation};
+    use crate::solver::tests::status;
+    use crate::solver::{cast_normal, finish, quality};
+    use crate::Actions;
+
+    #[test]
+    fn frontier() {
+        let s = status(40, 20);
+        let points = solve(&s);
+        assert!(!points.is_empty());
+        for (i, p) in points.iter().enumerate() {
+            // 每个点都可以复现
+            let mut replay = s.clone();
+            let mut probability = 1.0;
+            for &action in &p.actions {
+                assert!(replay.is_action_allowed(action).is_ok());
+                probability *= replay.success_rate(action) as f64 / 100.0;
+                replay = cast_normal(&replay, action);
+            }
+            assert!(replay.progress >= s.recipe.difficulty);
+            assert_eq!(replay.quality.min(s.recipe.quality), p.quality);
+            assert_eq!(replay.craft_points, p.craft_points);
+            assert_eq!(p.actions.len(), p.steps);
+            assert!((probability - p.probability).abs() < 1e-9);
+            // 没有点被其他点支配
+            for q in &points[..i] {
+                assert!(
+                    !(q.quality >= p.quality
+                        && q.craft_points >= p.craft_points
+                        && q.steps <= p.steps
+                        && q.probability >= p.probability)
+                );
+            }
+        }
+
+        // 必定成功的点中包含品质最高的序列与工次最少的序列
+        let certain: Vec<_> = points.iter().filter(|p| p.probability == 1.0).collect();
+        let best = quality::solve(&s).unwrap().status.quality;
+        assert_eq!(certain.iter().map(|p| p.quality).max(), Some(best));
+        let fewest = finish::solve(&s, finish::Goal::FewestSteps).unwrap();
+        // 收尾求解按工次计数，专心致志等技能不计入工次
+        let steps = certain.iter().map(|p| p.steps).min().unwrap();
+        assert!((fewest.status.step as usize..=fewest.actions.len()).contains(&steps));
+        assert!(points.iter().any(|p| p.probability < 1.0));
+
+        assert!(solve(&status(0, 10)).is_empty());
+    }
+
+    #[test]
+    fn constraints() {
+        let s = status(40, 20);
+        let constraints = Constraints {
+            banned: vec![Actions::Groundwork],
+            prefix: vec![Actions::MuscleMemory],
+            max_steps: Some(5),
+            min_craft_points: 5,
+            no_risky: true,
+            ..Default::default()
+        };
+        let points = solve_with(&s, &constraints).unwrap();
+        assert!(!points.is_empty());
+        for p in &points {
+            assert_eq!(constraints.check(&s, &p.actions), Ok(()));
+            assert_eq!(p.actions.len(), p.steps);
+            assert_eq!(p.probability, 1.0);
+        }
+
+        let prefix = Constraints {
+            prefix: vec![Actions::Reflect, Actions::MuscleMemory],
+            ..Default::default()
+        };
+        assert!(matches!(
+            solve_with(&s, &prefix),
+            Err(Violation::IllegalAction { index: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+//! 求解随球色变化的最优策略。
+//!
+//! 与给出固定技能序列的求解器不同，策略对每一个可能到达的状态（包括其球色）
+//! 都给出一个技能，使最终结果的期望最高。
+//! 求解时按照 [`ConditionModel`] 枚举球色，有成功率的技能按成功与失败分支，
+//! 是一个在全部可达状态上进行的期望最大化搜索。
+//! 由于观察、秘诀等技能可以用来等待球色，可达状态的数量随制作力与耐久增长得非常快，
+//! 因此只适合在制作后期剩余资源不多的局面下使用。
+//! [`solve_with`] 还可以通过 [`Constraints`] 限制策略使用的技能。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::random::SplitMix64;
+//! use ffxiv_crafting::{data, solver, Attributes, ConditionModel, Recipe, Status};
+//! use ffxiv_crafting::objective::MaxQuality;
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 20,
+//!     specialist: true,
+//! };
+//! let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! recipe.durability = 20;
+//! let mut s = Status::new(attr, recipe);
+//! // 只剩两次制作的进展
+//! s.progress = recipe.difficulty - 560;
+//! s.step = 10;
+//! let policy = solver::policy::solve(&s, &MaxQuality);
+//! println!("期望品质：{}", policy.expected_value());
+//!
+//! // 按照策略进行一次随机的制作
+//! let model = ConditionModel::from_status(&s);
+//! let mut rng = SplitMix64::new(1);
+//! while let Some(action) = policy.action(&s) {
+//!     s.try_step_random(action, &model, &mut rng).unwrap();
+//! }
+//! assert!(s.is_finished());
+//! ```
+use std::collections::HashMap;
+
+use super::candidate_actions;
+use super::constraint::Constraints;
+use crate::objective::Objective;
+use crate::{Actions, ConditionModel, StateKey, Status};
+
+/// 求解得到的策略
+#[derive(Debug)]
+pub struct Policy {
+    root: Status,
+    uses_step: bool,
+    table: HashMap<StateKey, (f64, Option<Actions>)>,
+}
+
+impl Policy {
+    /// 从求解时的初始状态开始，按照策略制作所得分数的期望
+    pub fn expected_value(&self) -> f64 {
+        self.value(&self.root).unwrap_or(0.0)
+    }
+
+    /// 状态`s`下应当发动的技能。
+    /// 制作已经结束，或`s`不是从初始状态可能到达的状态时返回None。
+    pub fn action(&self, s: &Status) -> Option<Actions> {
+        self.table
+            .get(&normalize(s.clone(), self.uses_step).key())?
+            .1
+    }
+
+    /// 从状态`s`开始按照策略制作所得分数的期望。
+    /// `s`不是从初始状态可能到达的状态时返回None。
+    pub fn value(&self, s: &Status) -> Option<f64> {
+        Some(
+            self.table
+                .get(&normalize(s.clone(), self.uses_step).key())?
+                .0,
+        )
+    }
+
+    /// 策略覆盖的状态数
+    pub fn states(&self) -> usize {
+        self.table.len()
+    }
+}
+
+/// 求解从`status`开始使`objective`的期望最高的策略
+pub fn solve<O: Objective + ?Sized>(status: &Status, objective: &O) -> Policy {
+    solve_with(status, objective, &Constraints::default())
+}
+
+/// 求解从`status`开始只使用`constraints`允许的技能，并使`objective`的期望最高的策略。
+///
+/// 策略在每个状态下逐步决策，只使用`constraints`中对技能的限制，
+/// 前缀、技能数上限与结束时的剩余要求只适用于固定的技能序列，不会被使用。
+pub fn solve_with<O: Objective + ?Sized>(
+    status: &Status,
+    objective: &O,
+    constraints: &Constraints,
+) -> Policy {
+    let mut search = Search {
+        objective,
+        constraints,
+        model: ConditionModel::from_status(status),
+        uses_step: objective.uses_step(),
+        table: HashMap::new(),
+        open: HashMap::new(),
+        stack: Vec::new(),
+    };
+    search.value(&normalize(status.clone(), search.uses_step));
+    Policy {
+        root: status.clone(),
+        uses_step: search.uses_step,
+        table: search.table,
+    }
+}
+
+struct Search<'a, O: ?Sized> {
+    objective: &'a O,
+    constraints: &'a Constraints,
+    model: ConditionModel,
+    uses_step: bool,
+    table: HashMap<StateKey, (f64, Option<Actions>)>,
+    /// 尚未求解完的状态，及其在`stack`中的位置
+    open: HashMap<StateKey, usize>,
+    stack: Vec<Status>,
+}
+
+/// 状态所在的强连通分量尚未求解完时，分支指向的状态
+#[derive(Clone, Copy)]
+enum Target {
+    /// 同一强连通分量中的第几个状态
+    Member(usize),
+    /// 已经求解完的状态的价值
+    Known(f64),
+}
+
+/// 强连通分量中的一个状态下，每个技能的各分支
+type Edges = Vec<(Actions, Vec<(f64, Target)>)>;
+
+impl<O: Objective + ?Sized> Search<'_, O> {
+    /// 返回`s`的价值，以及搜索中遇到的尚未求解完的状态在`stack`中的最小位置
+    fn value(&mut self, s: &Status) -> (f64, usize) {
+        let key = s.key();
+        if let Some(&index) = self.open.get(&key) {
+            return (self.table[&key].0, index);
+        }
+        if let Some(&(value, _)) = self.table.get(&key) {
+            return (value, usize::MAX);
+        }
+        if s.is_finished() {
+            let value = self.objective.score(s);
+            self.table.insert(key, (value, None));
+            return (value, usize::MAX);
+        }
+        // 制作力已满时在高品质下使用秘诀，或是观察后再用秘诀补回制作力，都可能使状态回到之前。
+        // 这里按照Tarjan算法找出这样的强连通分量：搜索时回到尚未求解完的状态的分支先使用其暂定的价值，
+        // 整个分量搜索完后再迭代到不动点
+        let index = self.stack.len();
+        self.open.insert(key, index);
+        self.stack.push(s.clone());
+        self.table.insert(key, (f64::NEG_INFINITY, None));
+        let mut best = (f64::NEG_INFINITY, None);
+        let mut low = usize::MAX;
+        for action in candidate_actions(s, self.constraints) {
+            let Ok(branches) = s.transitions(action, &self.model) else {
+                continue;
+            };
+            let mut value = 0.0;
+            for (p, next) in branches {
+                let (v, l) = self.value(&normalize(next, self.uses_step));
+                value += p * v;
+                low = low.min(l);
+            }
+            if best.1.is_none() || value > best.0 {
+                best = (value, Some(action));
+            }
+        }
+        self.table.insert(key, best);
+        if low < index {
+            return (best.0, low);
+        }
+        if low == index {
+            self.solve_component(index);
+        }
+        for s in self.stack.drain(index..) {
+            self.open.remove(&s.key());
+        }
+        (self.table[&key].0, usize::MAX)
+    }
+
+    /// 求解`stack`中从`index`开始的强连通分量
+    fn solve_component(&mut self, index: usize) {
+        let members = &self.stack[index..];
+        let edges: Vec<Edges> = members
+            .iter()
+            .map(|s| {
+                candidate_actions(s, self.constraints)
+                    .filter_map(|action| {
+                        let branches = s.transitions(action, &self.model).ok()?;
+                        let targets = branches
+                            .into_iter()
+                            .map(|(p, next)| {
+                                let key = normalize(next, self.uses_step).key();
+                                let target = match self.open.get(&key) {
+                                    Some(&i) => Target::Member(i - index),
+                                    None => Target::Known(self.table[&key].0),
+                                };
+                                (p, target)
+                            })
+                            .collect();
+                        Some((action, targets))
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut values: Vec<_> = members.iter().map(|s| self.table[&s.key()]).collect();
+        fixed_point(&mut values, &edges);
+        for (s, value) in members.iter().zip(values) {
+            self.table.insert(s.key(), value);
+        }
+    }
+}
+
+/// 迭代求解强连通分量中各状态的价值与技能。
+///
+/// `values`是各状态的初始价值，不能高于实际的价值；`edges`是各状态下每个技能的各分支。
+/// 回到自身的分支直接按照 V = 其余分支的期望 / (1 - 回到自身的概率) 求解，
+/// 经过其他状态的循环则反复迭代，价值单调增加，直至收敛。
+/// 必定回到自身的技能永远无法结束制作，不会被选择。
+fn fixed_point(values: &mut [(f64, Option<Actions>)], edges: &[Edges]) {
+    loop {
+        let mut changed = false;
+        for (i, actions) in edges.iter().enumerate() {
+            for &(action, ref branches) in actions {
+                let mut rest = 0.0;
+                let mut stay = 0.0;
+                for &(p, target) in branches {
+                    match target {
+                        Target::Member(j) if j == i => stay += p,
+                        Target::Member(j) => rest += p * values[j].0,
+                        Target::Known(v) => rest += p * v,
+                    }
+                }
+                if stay > 1.0 - 1e-9 {
+                    continue;
+                }
+                let value = rest / (1.0 - stay);
+                let current = values[i].0;
+                if value > current
+                    && (current == f64::NEG_INFINITY
+                        || value - current > 1e-9 * current.abs().max(1.0))
+                {
+                    values[i] = (value, Some(action));
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// 剥离与之后的决策无关的信息：目标与工次数无关时，除“是否为第一工次”外的步数
+fn normalize(mut s: Status, uses_step: bool) -> Status {
+    if !uses_step {
+        s.step = s.step.min(1);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fixed_point, solve, solve_with, Target};
+    use crate::objective::{FewestSteps, HighQuality, MaxQuality, Objective};
+    use crate::random::SplitMix64;
+    use crate::solver::constraint::Constraints;
+    use crate::{data, solver, Actions, Attributes, Condition, ConditionModel, Recipe, Status};
+
+    /// 剩余进展为`left`次基础制作的状态
+    fn status(craft_points: i32, durability: u16, left: u16) -> Status {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4048,
+            control: 4005,
+            craft_points,
+            specialist: true,
+        };
+        let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        recipe.durability = durability;
+        let mut s = Status::new(attr, recipe);
+        s.progress = recipe.difficulty - s.caches.base_synth as u16 * left;
+        s.step = 1;
+        s
+    }
+
+    #[test]
+    fn follow_policy() {
+        let s = status(20, 20, 2);
+        let policy = solve(&s, &MaxQuality);
+        let model = ConditionModel::from_status(&s);
+        let mut rng = SplitMix64::new(5);
+        let runs = 5000;
+        let mut total = 0.0;
+        for _ in 0..runs {
+            let mut s = s.clone();
+            while let Some(action) = policy.action(&s) {
+                s.try_step_random(action, &model, &mut rng).unwrap();
+            }
+            assert!(s.is_finished());
+            if s.progress >= s.recipe.difficulty {
+                total += s.quality.min(s.recipe.quality) as f64;
+            }
+        }
+        let expected = policy.expected_value();
+        assert!(expected > 0.0);
+        assert!((total / runs as f64 - expected).abs() / expected < 0.03);
+    }
+
+    #[test]
+    fn no_risky() {
+        // 没有制作力时，只能先用仓促赌一次品质再完成制作
+        let mut s = status(0, 20, 1);
+        s.attributes.specialist = false;
+        let policy = solve(&s, &MaxQuality);
+        assert_eq!(policy.action(&s), Some(Actions::HastyTouch));
+        let constraints = Constraints {
+            no_risky: true,
+            ..Default::default()
+        };
+        let safe = solve_with(&s, &MaxQuality, &constraints);
+        assert_eq!(safe.action(&s), Some(Actions::BasicSynthesis));
+        assert!(safe.expected_value() < policy.expected_value());
+    }
+
+    #[test]
+    fn not_worse_than_sequence() {
+        // 只有通常球色时，策略不会差于固定的最优技能序列
+        let mut s = status(40, 20, 2);
+        s.recipe.conditions_flag = 1 << Condition::Normal as u16;
+        let policy = solve(&s, &MaxQuality);
+        let best = solver::quality::solve(&s).unwrap();
+        assert!(policy.expected_value() >= best.status.quality as f64 - 1e-6);
+
+        let policy = solve(&s, &HighQuality);
+        let hq = policy.expected_value();
+        assert!((0.0..=100.0).contains(&hq));
+        assert!(hq >= best.status.high_quality_probability().unwrap() as f64 - 1e-6);
+    }
+
+    #[test]
+    fn careful_observation() {
+        // 没有制作力时只能等待高品质发动秘诀，设计变动可以免费改变球色
+        let s = status(0, 20, 1);
+        assert_eq!(s.condition, Condition::Normal);
+        let policy = solve(&s, &MaxQuality);
+        assert_eq!(policy.action(&s), Some(Actions::CarefulObservation));
+
+        let mut other = s.clone();
+        other.attributes.specialist = false;
+        let without = solve(&other, &MaxQuality);
+        assert_ne!(without.action(&other), Some(Actions::CarefulObservation));
+        assert!(policy.expected_value() > without.expected_value());
+
+        let constraints = Constraints {
+            banned: vec![Actions::CarefulObservation],
+            ..Default::default()
+        };
+        let banned = solve_with(&s, &MaxQuality, &constraints);
+        assert_ne!(banned.action(&s), Some(Actions::CarefulObservation));
+        assert!(policy.expected_value() > banned.expected_value());
+    }
+
+    #[test]
+    fn step_dependent() {
+        // 只有坯料制作能在一个工次内完成制作，工次数不能在求解时剥离
+        let mut s = status(40, 40, 3);
+        s.recipe.conditions_flag = 1 << Condition::Normal as u16;
+        let best = solver::sequence::solve(&s, &FewestSteps).unwrap();
+        assert_eq!(best.actions, [Actions::Groundwork]);
+        let policy = solve(&s, &FewestSteps);
+        assert_eq!(policy.action(&s), Some(Actions::Groundwork));
+        assert!((policy.expected_value() - FewestSteps.score(&best.status)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cycle() {
+        // 状态0：秘诀有一半概率得到10分，一半概率到达状态1；直接制作得到6分。
+        // 状态1：一半概率回到状态0，一半概率停留在自身，因此 V1 = V0，
+        // V0 = max(6, 5 + V1 / 2)，不动点为 V0 = V1 = 10
+        let edges = vec![
+            vec![
+                (
+                    Actions::TricksOfTheTrade,
+                    vec![(0.5, Target::Known(10.0)), (0.5, Target::Member(1))],
+                ),
+                (Actions::BasicSynthesis, vec![(1.0, Target::Known(6.0))]),
+            ],
+            vec![(
+                Actions::Observe,
+                vec![(0.5, Target::Member(0)), (0.5, Target::Member(1))],
+            )],
+        ];
+        let mut values = [
+            (6.0, Some(Actions::BasicSynthesis)),
+            (f64::NEG_INFINITY, None),
+        ];
+        fixed_point(&mut values, &edges);
+        assert_eq!(values[0].1, Some(Actions::TricksOfTheTrade));
+        assert_eq!(values[1].1, Some(Actions::Observe));
+        for (value, _) in values {
+            assert!((value - 10.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! 求解白球（通常）状态下能完成制作且品质最高的技能序列。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::{data, solver, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 3258,
+//!     control: 3340,
+//!     craft_points: 100,
+//!     specialist: true,
+//! };
+//! let recipe = Recipe::new(data::recipe_level_table(512), 100, 100, 100);
+//! let s = Status::new(attr, recipe);
+//! let solution = solver::quality::solve(&s).unwrap();
+//! assert!(solution.status.progress >= recipe.difficulty);
+//! println!("{:?} => {}", solution.actions, solution.status.quality);
+//! ```
+use super::{sequence, Solution};
+use crate::objective::MaxQuality;
+use crate::Status;
+
+/// 求解从`status`开始，在之后的每个工次都是通常球色的前提下，
+/// 能够完成制作并使品质最高的技能序列。无法完成制作时返回None。
+///
+/// 等价于以 [`MaxQuality`] 为目标调用 [`sequence::solve`]：
+/// 搜索采用带记忆化的分支限界，
+/// 使用松弛后的动态规划估计每个子状态可达品质的上界，剪去不可能优于当前最优解的分支。
+///
+/// 结果是精确的最优解，但搜索量会随制作力与耐久的增加而迅速增长，
+/// 能在可接受的时间内求解的规模见 [`sequence`] 的说明，需要限制搜索量时使用 [`sequence::solve_limited`]。
+pub fn solve(status: &Status) -> Option<Solution> {
+    sequence::solve(status, &MaxQuality)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve;
+    use crate::objective::MaxQuality;
+    use crate::solver::constraint::Constraints;
+    use crate::solver::tests::{brute_force, status};
+    use crate::{Condition, Status};
+
+    /// 穷举得到的最高品质
+    fn best(s: &Status) -> Option<u32> {
+        brute_force(s, &MaxQuality, &Constraints::default()).map(|q| q as u32)
+    }
+
+    #[test]
+    fn optimal() {
+        for (craft_points, durability) in [(0, 40), (24, 20), (40, 20), (45, 30)] {
+            let s = status(craft_points, durability);
+            let solution = solve(&s);
+            assert_eq!(
+                solution
+                    .as_ref()
+                    .map(|x| x.status.quality.min(s.recipe.quality)),
+                best(&s),
+                "cp {} durability {}",
+                craft_points,
+                durability
+            );
+        }
+    }
+
+    #[test]
+    fn replay() {
+        let s = status(120, 40);
+        let solution = solve(&s).unwrap();
+        let mut replay = s.clone();
+        for &action in &solution.actions {
+            assert!(replay.is_action_allowed(action).is_ok());
+            replay.cast_action(action);
+        }
+        assert!(replay.progress >= s.recipe.difficulty);
+        assert_eq!(replay.quality, solution.status.quality);
+        assert!(replay.quality > 0);
+    }
+
+    #[test]
+    fn current_condition() {
+        let mut s = status(40, 20);
+        s.condition = Condition::Good;
+        // 只有当前工次是高品质，之后的工次按通常计算
+        let solution = solve(&s).unwrap();
+        assert_eq!(Some(solution.status.quality), best(&s));
+        assert_eq!(solution.status.condition, Condition::Normal);
+    }
+
+    #[test]
+    fn unfinishable() {
+        assert!(solve(&status(0, 10)).is_none());
+    }
+}
@@ -0,0 +1,476 @@
+//! 求解白球（通常）状态下使任意 [`Objective`] 最高的技能序列。
+//!
+//! 这里的分支限界搜索是所有白球求解器共用的引擎，
+//! [`quality`](super::quality) 与 [`finish`](super::finish) 只是以特定的目标调用它。
+//! 目标可能与品质、工次、剩余制作力等任意信息有关，
+//! 除了目标与工次数无关（见 [`Objective::uses_step`]）时可以合并只有工次数不同的状态外，
+//! 搜索时无法剥离状态中的任何部分，因此制作力较多时搜索量会迅速增长。
+//! 剪枝的效果取决于 [`Objective::upper_bound`] 给出的上界。
+//! [`solve_with`] 还可以附加 [`Constraints`] 限制可以使用的技能、技能数等。
+//!
+//! 搜索是精确的，耗时随制作力指数增长。以等级90、耐久70的配方求最高品质为例，
+//! release构建下制作力100约需1秒，200约需十几秒，250约需半分钟以上，
+//! 满制作力（约600点）的规模无法在可接受的时间内完成。
+//! 需要控制搜索量时使用 [`solve_limited`]，超过子状态数的上限时放弃搜索。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::objective::Collectable;
+//! use ffxiv_crafting::{data, solver, Attributes, Recipe, Status};
+//!
+//! let attr = Attributes {
+//!     level: 90,
+//!     craftsmanship: 4048,
+//!     control: 4005,
+//!     craft_points: 40,
+//!     specialist: true,
+//! };
+//! let mut recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! recipe.durability = 20;
+//! let s = Status::new(attr, recipe);
+//! let solution = solver::sequence::solve(&s, &Collectable { threshold: 500 }).unwrap();
+//! assert!(solution.status.progress >= recipe.difficulty);
+//! println!("{:?} => {}", solution.actions, solution.status.quality);
+//! ```
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use super::bound::Bounds;
+use super::constraint::{Constraints, Violation};
+use super::{cast_normal, deterministic_actions, Solution};
+use crate::objective::Objective;
+use crate::{Actions, StateKey, Status};
+
+/// 求解从`status`开始，在之后的每个工次都是通常球色的前提下，
+/// 能够完成制作并使`objective`最高的技能序列。无法完成制作时返回None。
+///
+/// 求解采用带记忆化的分支限界搜索，
+/// 使用松弛后的动态规划估计每个子状态可达品质的上界，再交给`objective`换算为分数的上界。
+pub fn solve<O: Objective + ?Sized>(status: &Status, objective: &O) -> Option<Solution> {
+    search(status, objective, &Constraints::default())
+}
+
+/// 搜索的子状态数超过了上限
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SearchLimitExceeded;
+
+impl Display for SearchLimitExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("search limit exceeded")
+    }
+}
+
+impl Error for SearchLimitExceeded {}
+
+/// 与 [`solve`] 相同，但最多展开`max_nodes`个子状态，超过时放弃搜索并返回错误。
+///
+/// 展开的子状态数大致与耗时成正比，release构建下每秒约二十万个。
+pub fn solve_limited<O: Objective + ?Sized>(
+    status: &Status,
+    objective: &O,
+    max_nodes: usize,
+) -> Result<Option<Solution>, SearchLimitExceeded> {
+    search_limited(status, objective, &Constraints::default(), max_nodes)
+}
+
+/// 求解从`status`开始满足`constraints`中全部约束，并使`objective`最高的技能序列，
+/// 之后的工次都按照通常球色计算。
+///
+/// 找不到满足全部约束的技能序列时，依次加入技能限制、技能数上限、剩余制作力与剩余耐久的要求，
+/// 返回第一个使问题无解的约束；前缀本身无法满足时返回前缀中的问题。
+pub fn solve_with<O: Objective + ?Sized>(
+    status: &Status,
+    objective: &O,
+    constraints: &Constraints,
+) -> Result<Solution, Violation> {
+    let start = constraints.apply_prefix(status)?;
+    if let Some(mut solution) = search(&start, objective, constraints) {
+        solution
+            .actions
+            .splice(0..0, constraints.prefix.iter().copied());
+        return Ok(solution);
+    }
+
+    let mut relaxed = Constraints {
+        prefix: constraints.prefix.clone(),
+        ..Default::default()
+    };
+    let infeasible = |c: &Constraints| search(&start, &Feasible, c).is_none();
+    if infeasible(&relaxed) {
+        return Err(Violation::Unfinished);
+    }
+    relaxed.allowed = constraints.allowed.clone();
+    relaxed.banned = constraints.banned.clone();
+    relaxed.no_risky = constraints.no_risky;
+    if infeasible(&relaxed) {
+        return Err(Violation::ActionsRestricted);
+    }
+    relaxed.max_steps = constraints.max_steps;
+    if infeasible(&relaxed) {
+        return Err(Violation::TooManySteps);
+    }
+    relaxed.min_craft_points = constraints.min_craft_points;
+    if infeasible(&relaxed) {
+        return Err(Violation::CraftPointsBelowMinimum);
+    }
+    Err(Violation::DurabilityBelowMinimum)
+}
+
+/// 分支限界搜索的入口，[`quality`](super::quality) 与 [`finish`](super::finish) 也通过它求解
+pub(crate) fn search<O: Objective + ?Sized>(
+    status: &Status,
+    objective: &O,
+    constraints: &Constraints,
+) -> Option<Solution> {
+    search_limited(status, objective, constraints, usize::MAX).unwrap_or(None)
+}
+
+fn search_limited<O: Objective + ?Sized>(
+    status: &Status,
+    objective: &O,
+    constraints: &Constraints,
+    max_nodes: usize,
+) -> Result<Option<Solution>, SearchLimitExceeded> {
+    let mut search = Search {
+        objective,
+        constraints,
+        max_actions: constraints.remaining_steps(),
+        bounds: Bounds::normal(status),
+        memo: HashMap::new(),
+        nodes: 0,
+        max_nodes,
+    };
+    // 上界按照通常球色估计，不适用于当前工次，因此不对初始状态做估计
+    let root = Node {
+        status: status.clone(),
+        actions: 0,
+    };
+    let value = search.value(&root, f64::INFINITY, INFEASIBLE);
+    if search.nodes > max_nodes {
+        return Err(SearchLimitExceeded);
+    }
+    if value == INFEASIBLE {
+        return Ok(None);
+    }
+
+    let mut node = root;
+    let mut actions = Vec::new();
+    while let Some(&Entry::Exact(_, Some(action))) = search.memo.get(&search.key(&node)) {
+        node = node.cast(action);
+        actions.push(action);
+    }
+    Ok(Some(Solution {
+        actions,
+        status: node.status,
+    }))
+}
+
+/// 只要求能够完成制作，用于找出无法满足的约束。
+/// 上界与分数相同，因此找到任意一个解后即停止搜索。
+struct Feasible;
+
+impl Objective for Feasible {
+    fn score(&self, _s: &Status) -> f64 {
+        0.0
+    }
+
+    fn upper_bound(&self, _s: &Status, _quality: u32) -> f64 {
+        0.0
+    }
+}
+
+const INFEASIBLE: f64 = f64::NEG_INFINITY;
+
+/// 搜索中的子状态。
+/// 专心致志等技能不消耗工次，因此技能数需要单独记录，不能使用[`Status::step`]
+struct Node {
+    status: Status,
+    /// 已经发动的技能数，不包括前缀
+    actions: usize,
+}
+
+impl Node {
+    fn cast(&self, action: Actions) -> Node {
+        Node {
+            status: cast_normal(&self.status, action),
+            actions: self.actions + 1,
+        }
+    }
+}
+
+enum Entry {
+    /// 子状态能达到的最高分数及达成它的第一个技能，无法完成制作时为[`INFEASIBLE`]
+    Exact(f64, Option<Actions>),
+    /// 子状态能达到的分数的上界
+    Upper(f64),
+}
+
+struct Search<'a, O: ?Sized> {
+    objective: &'a O,
+    constraints: &'a Constraints,
+    /// 技能数的上限
+    max_actions: Option<usize>,
+    bounds: Bounds,
+    memo: HashMap<(StateKey, usize), Entry>,
+    /// 已经展开的子状态数，超过`max_nodes`后放弃搜索
+    nodes: usize,
+    max_nodes: usize,
+}
+
+impl<O: Objective + ?Sized> Search<'_, O> {
+    /// 记忆化使用的键。
+    /// 没有技能数上限时技能数不影响结果，不计入键中；
+    /// 目标与工次数无关时，工次数只需区分是否为第一工次
+    fn key(&self, node: &Node) -> (StateKey, usize) {
+        let actions = if self.max_actions.is_some() {
+            node.actions
+        } else {
+            0
+        };
+        let mut key = node.status.key();
+        if !self.objective.uses_step() {
+            key.step = key.step.min(1);
+        }
+        (key, actions)
+    }
+
+    /// 已经结束的子状态的分数，不满足约束时为[`INFEASIBLE`]；子状态尚未结束时返回None
+    fn terminal(&self, node: &Node) -> Option<f64> {
+        let s = &node.status;
+        if s.progress >= s.recipe.difficulty {
+            let satisfied = s.craft_points >= self.constraints.min_craft_points
+                && s.durability >= self.constraints.min_durability;
+            return Some(if satisfied {
+                self.objective.score(s)
+            } else {
+                INFEASIBLE
+            });
+        }
+        if s.durability == 0 || self.max_actions.is_some_and(|n| node.actions >= n) {
+            return Some(INFEASIBLE);
+        }
+        None
+    }
+
+    /// 子状态能达到的分数的上界，优先使用之前搜索得到的结果
+    fn upper(&mut self, node: &Node) -> f64 {
+        if let Some(value) = self.terminal(node) {
+            return value;
+        }
+        let s = &node.status;
+        match self.memo.get(&self.key(node)) {
+            Some(&Entry::Exact(value, _)) => value,
+            Some(&Entry::Upper(upper)) => upper,
+            None => match self.bounds.quality(s) {
+                Some(gain) => self.objective.upper_bound(s, s.quality + gain),
+                None => INFEASIBLE,
+            },
+        }
+    }
+
+    /// 计算子状态能达到的最高分数，`upper`为该子状态的上界。
+    ///
+    /// 若结果大于`alpha`，返回精确值；否则返回一个不超过`alpha`的上界。
+    fn value(&mut self, node: &Node, upper: f64, alpha: f64) -> f64 {
+        if let Some(value) = self.terminal(node) {
+            return value;
+        }
+        let key = self.key(node);
+        match self.memo.get(&key) {
+            Some(&Entry::Exact(value, _)) => return value,
+            Some(&Entry::Upper(upper)) if upper <= alpha => return upper,
+            _ => {}
+        }
+        if upper <= alpha {
+            self.memo.insert(key, Entry::Upper(upper));
+            return upper;
+        }
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return INFEASIBLE;
+        }
+
+        // 优先搜索上界更高的分支，以便尽早找到较好的解
+        let mut children = Vec::new();
+        for action in deterministic_actions(&node.status, self.constraints) {
+            let next = node.cast(action);
+            let upper = self.upper(&next);
+            if upper != INFEASIBLE {
+                children.push((upper, action, next));
+            }
+        }
+        children.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut best = (INFEASIBLE, None);
+        // 所有未能超过alpha的分支的上界中的最大值
+        let mut soft = INFEASIBLE;
+        for (upper, action, next) in children {
+            let threshold = alpha.max(best.0);
+            if upper <= threshold {
+                soft = soft.max(upper);
+                break;
+            }
+            let value = self.value(&next, upper, threshold);
+            if value > threshold {
+                best = (value, Some(action));
+            } else {
+                soft = soft.max(value);
+            }
+        }
+        if best.0 > alpha {
+            self.memo.insert(key, Entry::Exact(best.0, best.1));
+            best.0
+        } else {
+            self.memo.insert(key, Entry::Upper(soft));
+            soft
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve_limited, solve_with, SearchLimitExceeded};
+    use crate::objective::{Collectable, FewestSteps, MaxQuality, MinCraftPoints, Objective};
+    use crate::solver::constraint::{Constraints, Violation};
+    use crate::solver::tests::status;
+    use crate::solver::{finish, quality};
+    use crate::{Actions, Status};
+
+    #[test]
+    fn matches_specialized() {
+        for (craft_points, durability) in [(24, 20), (40, 20), (45, 30)] {
+            let s = status(craft_points, durability);
+            let best = quality::solve(&s).map(|x| x.status.quality);
+            let solution = solve(&s, &MaxQuality);
+            assert_eq!(solution.map(|x| x.status.quality), best);
+
+            let least = finish::solve(&s, finish::Goal::LeastCraftPoints);
+            let solution = solve(&s, &MinCraftPoints);
+            assert_eq!(
+                solution.map(|x| x.status.craft_points),
+                least.map(|x| x.status.craft_points)
+            );
+
+            let fewest = finish::solve(&s, finish::Goal::FewestSteps);
+            let solution = solve(&s, &FewestSteps);
+            assert_eq!(
+                solution.map(|x| x.status.step),
+                fewest.map(|x| x.status.step)
+            );
+        }
+    }
+
+    #[test]
+    fn replay() {
+        let s = status(40, 20);
+        let threshold = quality::solve(&s).unwrap().status.quality;
+        let solution = solve(&s, &Collectable { threshold }).unwrap();
+        let mut replay = s.clone();
+        for &action in &solution.actions {
+            assert!(replay.is_action_allowed(action).is_ok());
+            replay.cast_action(action);
+        }
+        assert!(replay.progress >= s.recipe.difficulty);
+        assert!(replay.quality >= threshold);
+
+        // 闭包作为目标：完成制作并使耐久剩余最多
+        let solution = solve(&s, &|s: &Status| s.durability as f64).unwrap();
+        assert!(solution.status.progress >= s.recipe.difficulty);
+        assert!(solve(&status(0, 10), &MaxQuality).is_none());
+    }
+
+    #[test]
+    fn constraints() {
+        let s = status(40, 20);
+        let constraints = Constraints {
+            banned: vec![Actions::Groundwork, Actions::Reflect],
+            prefix: vec![Actions::MuscleMemory, Actions::Veneration],
+            max_steps: Some(6),
+            min_craft_points: 5,
+            ..Default::default()
+        };
+        let solution = solve_with(&s, &MaxQuality, &constraints).unwrap();
+        assert_eq!(constraints.check(&s, &solution.actions), Ok(()));
+        assert!(solution.status.craft_points >= 5);
+
+        let fail =
+            |constraints: Constraints| solve_with(&s, &MaxQuality, &constraints).unwrap_err();
+        assert_eq!(
+            fail(Constraints {
+                allowed: Some(vec![Actions::BasicSynthesis]),
+                ..Default::default()
+            }),
+            Violation::ActionsRestricted
+        );
+        assert_eq!(
+            fail(Constraints {
+                max_steps: Some(1),
+                ..Default::default()
+            }),
+            Violation::TooManySteps
+        );
+        assert_eq!(
+            fail(Constraints {
+                min_craft_points: 41,
+                ..Default::default()
+            }),
+            Violation::CraftPointsBelowMinimum
+        );
+        assert_eq!(
+            fail(Constraints {
+                min_durability: 25,
+                ..Default::default()
+            }),
+            Violation::DurabilityBelowMinimum
+        );
+        assert!(matches!(
+            fail(Constraints {
+                prefix: vec![Actions::Reflect, Actions::MuscleMemory],
+                ..Default::default()
+            }),
+            Violation::IllegalAction { index: 1, .. }
+        ));
+        assert_eq!(
+            solve_with(&status(0, 10), &MaxQuality, &Constraints::default()).unwrap_err(),
+            Violation::Unfinished
+        );
+    }
+
+    #[test]
+    fn max_steps_counts_actions() {
+        // 专心致志与快速改革不消耗工次，但仍然计入技能数
+        let s = status(40, 20);
+        for max_steps in 1..=6 {
+            let constraints = Constraints {
+                max_steps: Some(max_steps),
+                ..Default::default()
+            };
+            let objectives: [&dyn Objective; 3] = [&MaxQuality, &FewestSteps, &MinCraftPoints];
+            for objective in objectives {
+                match solve_with(&s, objective, &constraints) {
+                    Ok(solution) => {
+                        assert!(solution.actions.len() <= max_steps);
+                        assert_eq!(constraints.check(&s, &solution.actions), Ok(()));
+                    }
+                    Err(violation) => assert_eq!(violation, Violation::TooManySteps),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limited() {
+        let s = status(40, 20);
+        assert_eq!(
+            solve_limited(&s, &MaxQuality, 10).unwrap_err(),
+            SearchLimitExceeded
+        );
+        let best = solve(&s, &MaxQuality).map(|x| x.actions);
+        let limited = solve_limited(&s, &MaxQuality, 1_000_000).unwrap();
+        assert_eq!(limited.map(|x| x.actions), best);
+        assert!(solve_limited(&status(0, 10), &MaxQuality, 1_000_000)
+            .unwrap()
+            .is_none());
+    }
+}
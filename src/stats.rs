@@ -0,0 +1,439 @@
+//! 属性需求分析。
+//!
+//! - [`min_attributes`]：对于固定的配方与技能序列，计算使技能序列能够完整发动并达到要求所需的最低属性；
+//! - [`breakpoints`]：列出技能推动的进展或品质发生变化的属性值，
+//!   [`count_breakpoints`]则给出重复发动技能达到目标所需次数发生变化的属性值。
+//!
+//! 计算时直接调用模拟器，所有工次都按照通常球色计算，
+//! 因此 [`Caches::new`](crate::Caches::new) 中的取整以及buff带来的分段变化都会被准确地考虑。
+//!
+//! Example:
+//! ```rust
+//! use ffxiv_crafting::stats::{self, Requirement};
+//! use ffxiv_crafting::{data, Actions, Recipe, Status};
+//!
+//! let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+//! let actions = [
+//!     Actions::MuscleMemory,
+//!     Actions::Veneration,
+//!     Actions::Groundwork,
+//!     Actions::Groundwork,
+//!     Actions::Groundwork,
+//! ];
+//! let attr = stats::min_attributes(90, false, &recipe, &actions, Requirement::Completion).unwrap();
+//! println!("{:?}", attr);
+//! assert!(Status::new(attr, recipe).simulate(&actions).completed);
+//! ```
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "serde-support")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Actions, Attributes, Buffs, Recipe, SimulationReport, Status};
+
+/// 属性搜索的上限
+const LIMIT: i32 = 1 << 16;
+
+/// 技能序列需要达到的要求
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Requirement {
+    /// 完成制作
+    Completion,
+    /// 完成制作且品质不低于给定值
+    Quality(u32),
+    /// 完成制作且HQ概率（百分数）不低于给定值
+    HighQuality(i32),
+}
+
+impl Requirement {
+    /// 模拟结果是否满足要求。技能序列中出现无法发动的技能时视为不满足
+    pub fn is_met(&self, report: &SimulationReport) -> bool {
+        report.illegal.is_none()
+            && report.completed
+            && match *self {
+                Requirement::Completion => true,
+                Requirement::Quality(quality) => report.quality >= quality,
+                Requirement::HighQuality(percent) => report.high_quality >= percent,
+            }
+    }
+}
+
+/// 计算等级为`level`的玩家使用`actions`制作`recipe`并满足`requirement`所需的最低属性，
+/// 即使属性再高也无法满足时返回None。
+/// `specialist`为true时按专家计算，此时结果中的属性已经包含专家之证的加成；
+/// 技能序列中含有设计变动或专心致志时必须为true。
+///
+/// 作业精度越高，进展越早推满，之后的技能不会再发动，反而可能使品质降低，
+/// 因此先求出能完成制作的最低作业精度，再在此基础上依次求出最低的加工精度与制作力。
+/// 结果中的每一项都是在其他两项固定时的最小值。
+pub fn min_attributes(
+    level: u8,
+    specialist: bool,
+    recipe: &Recipe,
+    actions: &[Actions],
+    requirement: Requirement,
+) -> Option<Attributes> {
+    let met = |attributes: Attributes, requirement: Requirement| {
+        requirement.is_met(&Status::new(attributes, *recipe).simulate(actions))
+    };
+    let mut attributes = Attributes {
+        level,
+        craftsmanship: LIMIT,
+        control: LIMIT,
+        craft_points: LIMIT,
+        specialist,
+    };
+    if !met(attributes, Requirement::Completion) {
+        return None;
+    }
+    attributes.craftsmanship = lower_bound(|craftsmanship| {
+        met(
+            Attributes {
+                craftsmanship,
+                ..attributes
+            },
+            Requirement::Completion,
+        )
+    });
+    if !met(attributes, requirement) {
+        return None;
+    }
+    attributes.control = lower_bound(|control| {
+        met(
+            Attributes {
+                control,
+                ..attributes
+            },
+            requirement,
+        )
+    });
+    attributes.craft_points = lower_bound(|craft_points| {
+        met(
+            Attributes {
+                craft_points,
+                ..attributes
+            },
+            requirement,
+        )
+    });
+    Some(attributes)
+}
+
+/// 分段分析所针对的属性
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stat {
+    /// 作业精度，影响进展
+    Craftsmanship,
+    /// 加工精度，影响品质
+    Control,
+}
+
+/// 属性的分段点
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// 属性值，从这里开始结果发生变化
+    pub stat: i32,
+    /// 从该属性值开始技能推动的进展或增加的品质，
+    /// 在 [`count_breakpoints`] 中则为达到目标所需的次数
+    pub value: u32,
+}
+
+/// 列出`stat`在`range`内变化时，`action`推动的进展（作业精度）或增加的品质（加工精度）
+/// 发生变化的每一个属性值，第一项总是`range`的起点。
+///
+/// `attributes`给出玩家等级以及不被分析的其他属性，技能在`buffs`下以通常球色发动，
+/// 结果不受配方难度与最高品质的限制。
+///
+/// Example:
+/// ```rust
+/// use ffxiv_crafting::stats::{self, Stat};
+/// use ffxiv_crafting::{data, Actions, Attributes, Buffs, Recipe};
+///
+/// let attr = Attributes {
+///     level: 90,
+///     craftsmanship: 4000,
+///     control: 4000,
+///     craft_points: 600,
+///     specialist: false,
+/// };
+/// let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+/// let points = stats::breakpoints(
+///     &attr,
+///     &recipe,
+///     Actions::Groundwork,
+///     Buffs::default(),
+///     Stat::Craftsmanship,
+///     3800..=4200,
+/// );
+/// for p in &points {
+///     println!("作业精度达到{}后坯料制作推动{}进展", p.stat, p.value);
+/// }
+///
+/// // 两次坯料制作推满剩余进展所需的作业精度
+/// let remaining = recipe.difficulty as u32 / 2;
+/// for p in stats::count_breakpoints(&points, remaining) {
+///     println!("作业精度达到{}后需要{}次坯料制作", p.stat, p.value);
+/// }
+/// ```
+pub fn breakpoints(
+    attributes: &Attributes,
+    recipe: &Recipe,
+    action: Actions,
+    buffs: Buffs,
+    stat: Stat,
+    range: RangeInclusive<i32>,
+) -> Vec<Breakpoint> {
+    let (start, end) = (*range.start(), *range.end());
+    if start > end {
+        return Vec::new();
+    }
+    let mut recipe = *recipe;
+    recipe.difficulty = u16::MAX;
+    recipe.quality = u32::MAX;
+    let value = |x: i32| {
+        let attributes = match stat {
+            Stat::Craftsmanship => Attributes {
+                craftsmanship: x,
+                ..*attributes
+            },
+            Stat::Control => Attributes {
+                control: x,
+                ..*attributes
+            },
+        };
+        let mut s = Status::new(attributes, recipe);
+        s.buffs = buffs;
+        let outcome = s.cast_action_with_outcome(action);
+        match stat {
+            Stat::Craftsmanship => outcome.progress as u32,
+            Stat::Control => outcome.quality,
+        }
+    };
+
+    let mut points = vec![Breakpoint {
+        stat: start,
+        value: value(start),
+    }];
+    split(
+        &value,
+        (start, points[0].value),
+        (end, value(end)),
+        &mut points,
+    );
+    points
+}
+
+/// 由 [`breakpoints`] 的结果计算重复发动技能凑满`target`点进展或品质所需的次数，
+/// 列出次数发生变化的每一个属性值。技能效果为0的区间不计入结果。
+pub fn count_breakpoints(points: &[Breakpoint], target: u32) -> Vec<Breakpoint> {
+    let mut counts: Vec<Breakpoint> = Vec::new();
+    for p in points.iter().filter(|p| p.value > 0) {
+        let count = target.div_ceil(p.value);
+        if counts.last().is_none_or(|c| c.value != count) {
+            counts.push(Breakpoint {
+                stat: p.stat,
+                value: count,
+            });
+        }
+    }
+    counts
+}
+
+/// 在`(lo, hi]`中二分查找单调不减的`value`发生变化的位置，按顺序加入`points`
+fn split(
+    value: &impl Fn(i32) -> u32,
+    lo: (i32, u32),
+    hi: (i32, u32),
+    points: &mut Vec<Breakpoint>,
+) {
+    if lo.1 == hi.1 {
+        return;
+    }
+    if hi.0 - lo.0 == 1 {
+        points.push(Breakpoint {
+            stat: hi.0,
+            value: hi.1,
+        });
+        return;
+    }
+    let mid = lo.0 + (hi.0 - lo.0) / 2;
+    let mid = (mid, value(mid));
+    split(value, lo, mid, points);
+    split(value, mid, hi, points);
+}
+
+/// 在`0..=LIMIT`中二分查找使`f`成立的最小值，`f`需要单调且`f(LIMIT)`成立
+fn lower_bound(f: impl Fn(i32) -> bool) -> i32 {
+    let (mut lo, mut hi) = (0, LIMIT);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if f(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{breakpoints, count_breakpoints, min_attributes, Requirement, Stat};
+    use crate::{data, Actions, Attributes, Buffs, Recipe, Status};
+
+    #[test]
+    fn minimal() {
+        let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        let actions = [
+            Actions::MuscleMemory,
+            Actions::Manipulation,
+            Actions::Veneration,
+            Actions::WasteNotII,
+            Actions::Groundwork,
+            Actions::Groundwork,
+            Actions::PreparatoryTouch,
+            Actions::PreparatoryTouch,
+            Actions::Innovation,
+            Actions::PreparatoryTouch,
+            Actions::PreparatoryTouch,
+            Actions::GreatStrides,
+            Actions::ByregotsBlessing,
+            Actions::CarefulSynthesis,
+            Actions::CarefulSynthesis,
+        ];
+        let met = |attr: Attributes, requirement: Requirement| {
+            requirement.is_met(&Status::new(attr, recipe).simulate(&actions))
+        };
+        for requirement in [
+            Requirement::Completion,
+            Requirement::Quality(4000),
+            Requirement::HighQuality(50),
+        ] {
+            let attr = min_attributes(90, false, &recipe, &actions, requirement).unwrap();
+            assert!(met(attr, requirement), "{:?}", requirement);
+            for lower in [
+                Attributes {
+                    craftsmanship: attr.craftsmanship - 1,
+                    ..attr
+                },
+                Attributes {
+                    control: attr.control - 1,
+                    ..attr
+                },
+                Attributes {
+                    craft_points: attr.craft_points - 1,
+                    ..attr
+                },
+            ] {
+                let negative =
+                    lower.craftsmanship < 0 || lower.control < 0 || lower.craft_points < 0;
+                assert!(
+                    negative || !met(lower, requirement),
+                    "{:?} {:?}",
+                    requirement,
+                    lower
+                );
+            }
+        }
+
+        // 品质上限以上的要求无法满足
+        let requirement = Requirement::Quality(recipe.quality + 1);
+        assert!(min_attributes(90, false, &recipe, &actions, requirement).is_none());
+        // 比尔格的祝福需要内静，无论属性多高都无法发动
+        let actions = [Actions::ByregotsBlessing, Actions::Groundwork];
+        assert!(min_attributes(90, false, &recipe, &actions, Requirement::Completion).is_none());
+
+        // 专心致志只有专家才能使用
+        let actions = [
+            Actions::HeartAndSoul,
+            Actions::Veneration,
+            Actions::IntensiveSynthesis,
+            Actions::Groundwork,
+        ];
+        assert!(min_attributes(90, false, &recipe, &actions, Requirement::Completion).is_none());
+        let attr = min_attributes(90, true, &recipe, &actions, Requirement::Completion).unwrap();
+        assert!(attr.specialist);
+        let report = Status::new(attr, recipe).simulate(&actions);
+        assert!(Requirement::Completion.is_met(&report));
+    }
+
+    #[test]
+    fn breakpoints_brute_force() {
+        let attr = Attributes {
+            level: 90,
+            craftsmanship: 4000,
+            control: 4000,
+            craft_points: 600,
+            specialist: false,
+        };
+        let recipe = Recipe::new(data::recipe_level_table(560), 100, 100, 100);
+        let buffs = Buffs {
+            inner_quiet: 5,
+            innovation: 2,
+            veneration: 2,
+            ..Default::default()
+        };
+        for (action, stat) in [
+            (Actions::Groundwork, Stat::Craftsmanship),
+            (Actions::DelicateSynthesis, Stat::Craftsmanship),
+            (Actions::BasicTouch, Stat::Control),
+            (Actions::ByregotsBlessing, Stat::Control),
+        ] {
+            let range = 3900..=4100;
+            let points = breakpoints(&attr, &recipe, action, buffs, stat, range.clone());
+            assert_eq!(points[0].stat, 3900);
+            assert!(points.len() > 2, "{:?}", action);
+
+            let mut last = None;
+            let mut expected = Vec::new();
+            for x in range {
+                let mut s = Status::new(
+                    match stat {
+                        Stat::Craftsmanship => Attributes {
+                            craftsmanship: x,
+                            ..attr
+                        },
+                        Stat::Control => Attributes { control: x, ..attr },
+                    },
+                    recipe,
+                );
+                s.buffs = buffs;
+                s.cast_action(action);
+                let value = match stat {
+                    Stat::Craftsmanship => s.progress as u32,
+                    Stat::Control => s.quality,
+                };
+                if last != Some(value) {
+                    expected.push((x, value));
+                    last = Some(value);
+                }
+            }
+            let points: Vec<_> = points.iter().map(|p| (p.stat, p.value)).collect();
+            assert_eq!(points, expected, "{:?}", action);
+        }
+
+        // 需要的次数随属性增加而减少，且每个分段点上恰好够用
+        let points = breakpoints(
+            &attr,
+            &recipe,
+            Actions::Groundwork,
+            Buffs::default(),
+            Stat::Craftsmanship,
+            2000..=5000,
+        );
+        let target = recipe.difficulty as u32;
+        let counts = count_breakpoints(&points, target);
+        assert!(counts.len() > 1);
+        for pair in counts.windows(2) {
+            assert!(pair[1].value < pair[0].value);
+        }
+        for c in &counts {
+            let p = points.iter().rev().find(|p| p.stat <= c.stat).unwrap();
+            assert!(p.value * c.value >= target);
+            assert!(p.value * (c.value - 1) < target);
+        }
+    }
+}